@@ -0,0 +1,145 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use color_eyre::{Result, eyre::eyre};
+use serde::{Deserialize, Serialize};
+use std::{env, str::FromStr};
+
+use crate::{search::Search, task::Task};
+
+/// Environment variable used to pick the auto-archive rule when no flag is given
+pub const AUTO_ARCHIVE_ENV: &str = "KANBAN_ARCHIVE_AFTER";
+
+/// A card taken off the board. It can be put back on any column
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedTask {
+    pub task: Task,
+    /// Title of the column the card was archived from, where it goes back by default
+    pub column: String,
+    pub archived_at: DateTime<Utc>,
+}
+
+impl ArchivedTask {
+    pub fn new(task: Task, column: &str) -> Self {
+        ArchivedTask {
+            task,
+            column: column.to_string(),
+            archived_at: Utc::now(),
+        }
+    }
+}
+
+/// Cards archived from a board, the most recent first. Saved together with the board
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Archive {
+    cards: Vec<ArchivedTask>,
+}
+
+impl Archive {
+    pub fn insert(&mut self, i: usize, card: ArchivedTask) {
+        self.cards.insert(i, card)
+    }
+
+    pub fn remove(&mut self, i: usize) -> ArchivedTask {
+        self.cards.remove(i)
+    }
+
+    pub fn get(&self, i: usize) -> Option<&ArchivedTask> {
+        self.cards.get(i)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
+    /// Indexes of the cards matching `search`, or of every card when there is no search
+    pub fn search(&self, search: Option<&Search>) -> Vec<usize> {
+        self.cards
+            .iter()
+            .enumerate()
+            .filter(|(_, card)| search.is_none_or(|search| search.matches(&card.task)))
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+/// When the cards of the last column, the done one, are archived on their own
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum AutoArchive {
+    /// Cards stay until they are archived by hand
+    #[default]
+    Off,
+    /// Cards done for more than this many days are archived when the board is opened
+    After(u32),
+}
+
+impl FromStr for AutoArchive {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" | "never" => Ok(AutoArchive::Off),
+            days => days.parse().map(AutoArchive::After).map_err(|_| {
+                eyre!("Invalid auto-archive rule '{s}', expected a number of days or off")
+            }),
+        }
+    }
+}
+
+impl AutoArchive {
+    /// Rule chosen with the `--archive-after` flag, then the `KANBAN_ARCHIVE_AFTER` variable
+    pub fn resolve(flag: Option<AutoArchive>) -> Result<AutoArchive> {
+        match (flag, env::var(AUTO_ARCHIVE_ENV)) {
+            (Some(rule), _) => Ok(rule),
+            (None, Ok(value)) if !value.is_empty() => value.parse(),
+            _ => Ok(AutoArchive::default()),
+        }
+    }
+
+    /// Whether `task`, sitting in the done column, is old enough to be archived
+    pub fn is_due(self, task: &Task, now: DateTime<Utc>) -> bool {
+        match self {
+            AutoArchive::Off => false,
+            AutoArchive::After(days) => {
+                let done_since = task.moved_at.unwrap_or(task.created_at);
+                now - done_since > TimeDelta::days(days.into())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_keeps_archive_order() {
+        let mut archive = Archive::default();
+        for title in ["Fix login", "Write docs", "Fix signup"] {
+            let card = ArchivedTask::new(Task::new(title.to_string()), "Done");
+            archive.insert(0, card);
+        }
+        assert_eq!(archive.search(None), [0, 1, 2]);
+        let search = Search::parse("fix").unwrap().unwrap();
+        assert_eq!(archive.search(Some(&search)), [0, 2]);
+        assert_eq!(archive.remove(0).task.title, "Fix signup");
+    }
+
+    #[test]
+    fn test_auto_archive_rule() {
+        let now = Utc::now();
+        let mut task = Task::new("Task 1".to_string());
+        task.moved_at = Some(now - TimeDelta::days(8));
+
+        assert!(AutoArchive::After(7).is_due(&task, now));
+        assert!(!AutoArchive::After(10).is_due(&task, now));
+        assert!(!AutoArchive::Off.is_due(&task, now));
+
+        assert_eq!("14".parse::<AutoArchive>().unwrap(), AutoArchive::After(14));
+        assert_eq!("Off".parse::<AutoArchive>().unwrap(), AutoArchive::Off);
+        assert!("soon".parse::<AutoArchive>().is_err());
+    }
+}
@@ -0,0 +1,131 @@
+use color_eyre::{Result, eyre::eyre};
+use std::path::PathBuf;
+
+use crate::{archive::AutoArchive, persistence::StorageKind, wip::WipPolicy};
+
+/// Options accepted on the command line
+#[derive(Debug, Default, PartialEq)]
+pub struct Cli {
+    /// Board to open on startup. Created when it doesn't exist yet
+    pub board: Option<String>,
+    /// Workspace file to use instead of the default location
+    pub data_file: Option<PathBuf>,
+    /// Storage backend to use instead of guessing it from the data file
+    pub storage: Option<StorageKind>,
+    /// Whether a full column refuses new cards or just warns
+    pub wip_policy: Option<WipPolicy>,
+    /// Key bindings file to use instead of the one in the config directory
+    pub keymap: Option<PathBuf>,
+    /// Built-in or user theme to draw the board with
+    pub theme: Option<String>,
+    /// Days a card stays in the done column before it is archived on its own
+    pub archive_after: Option<AutoArchive>,
+}
+
+impl Cli {
+    pub fn parse() -> Result<Self> {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    /// Parses the given arguments, program name excluded
+    pub fn parse_from(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut cli = Cli::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .filter(|value| !value.is_empty())
+                    .ok_or_else(|| eyre!("Missing value for {flag}"))
+            };
+
+            match flag.as_str() {
+                "--board" => cli.board = Some(value()?),
+                "--data-file" => cli.data_file = Some(PathBuf::from(value()?)),
+                "--storage" => cli.storage = Some(value()?.parse()?),
+                "--wip-policy" => cli.wip_policy = Some(value()?.parse()?),
+                "--keymap" => cli.keymap = Some(PathBuf::from(value()?)),
+                "--theme" => cli.theme = Some(value()?),
+                "--archive-after" => cli.archive_after = Some(value()?.parse()?),
+                _ => return Err(eyre!("Unknown argument: {flag}")),
+            }
+        }
+
+        Ok(cli)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Cli> {
+        Cli::parse_from(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_board_flag() {
+        assert_eq!(
+            parse(&["--board", "Work"]).unwrap().board.as_deref(),
+            Some("Work")
+        );
+        assert_eq!(
+            parse(&["--board=Work"]).unwrap().board.as_deref(),
+            Some("Work")
+        );
+        assert_eq!(parse(&[]).unwrap(), Cli::default());
+    }
+
+    #[test]
+    fn test_data_file_flag() {
+        let cli = parse(&["--data-file", "/tmp/board.json", "--board", "Work"]).unwrap();
+        assert_eq!(cli.data_file, Some(PathBuf::from("/tmp/board.json")));
+        assert_eq!(cli.board.as_deref(), Some("Work"));
+    }
+
+    #[test]
+    fn test_storage_flag() {
+        let cli = parse(&["--storage=sqlite"]).unwrap();
+        assert_eq!(cli.storage, Some(StorageKind::Sqlite));
+        assert!(parse(&["--storage", "csv"]).is_err());
+    }
+
+    #[test]
+    fn test_wip_policy_flag() {
+        let cli = parse(&["--wip-policy", "refuse"]).unwrap();
+        assert_eq!(cli.wip_policy, Some(WipPolicy::Refuse));
+        assert!(parse(&["--wip-policy=never"]).is_err());
+    }
+
+    #[test]
+    fn test_keymap_flag() {
+        let cli = parse(&["--keymap=keys.json"]).unwrap();
+        assert_eq!(cli.keymap, Some(PathBuf::from("keys.json")));
+    }
+
+    #[test]
+    fn test_theme_flag() {
+        let cli = parse(&["--theme", "light"]).unwrap();
+        assert_eq!(cli.theme.as_deref(), Some("light"));
+        assert!(parse(&["--theme"]).is_err());
+    }
+
+    #[test]
+    fn test_archive_after_flag() {
+        let cli = parse(&["--archive-after=30"]).unwrap();
+        assert_eq!(cli.archive_after, Some(AutoArchive::After(30)));
+        assert!(parse(&["--archive-after", "-1"]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        assert!(parse(&["--board"]).is_err());
+        assert!(parse(&["--colour"]).is_err());
+    }
+}
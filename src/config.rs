@@ -0,0 +1,87 @@
+use color_eyre::{Result, eyre::eyre};
+use serde::de::DeserializeOwned;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::helpers::xdg_dir;
+
+/// Directory holding the user settings, `$XDG_CONFIG_HOME/kanban` or `~/.config/kanban`
+pub fn config_dir() -> Option<PathBuf> {
+    let config_home = env::var_os("XDG_CONFIG_HOME").map(PathBuf::from);
+    let home = env::var_os("HOME").map(PathBuf::from);
+    config_dir_from(config_home, home)
+}
+
+/// Same as `config_dir` but with every input given explicitly
+pub fn config_dir_from(config_home: Option<PathBuf>, home: Option<PathBuf>) -> Option<PathBuf> {
+    xdg_dir(config_home, home, ".config").map(|dir| dir.join("kanban"))
+}
+
+/// Settings file `name` in the config directory, written either as `.toml` or as `.json`
+pub fn find(name: &str) -> Option<PathBuf> {
+    let dir = config_dir()?;
+    ["toml", "json"]
+        .iter()
+        .map(|extension| dir.join(format!("{name}.{extension}")))
+        .find(|path| path.is_file())
+}
+
+/// Reads a settings file, as JSON when its extension is `.json` and as TOML otherwise
+pub fn read<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let text = fs::read_to_string(path)
+        .map_err(|error| eyre!("Can't read {}: {error}", path.display()))?;
+    let parsed = if path
+        .extension()
+        .is_some_and(|extension| extension == "json")
+    {
+        serde_json::from_str(&text).map_err(|error| eyre!("{error}"))
+    } else {
+        toml::from_str(&text).map_err(|error| eyre!("{error}"))
+    };
+    parsed.map_err(|error| eyre!("Invalid settings in {}: {error}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_config_dir() {
+        assert_eq!(
+            config_dir_from(Some(PathBuf::from("/xdg")), Some(PathBuf::from("/home/me"))),
+            Some(PathBuf::from("/xdg/kanban"))
+        );
+        // Relative XDG paths are ignored
+        assert_eq!(
+            config_dir_from(Some(PathBuf::from("xdg")), Some(PathBuf::from("/home/me"))),
+            Some(PathBuf::from("/home/me/.config/kanban"))
+        );
+        assert_eq!(config_dir_from(None, None), None);
+    }
+
+    #[test]
+    fn test_read_toml_and_json() {
+        let dir = env::temp_dir().join(format!("kanban-config-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let toml_file = dir.join("keys.toml");
+        let json_file = dir.join("keys.json");
+        fs::write(&toml_file, "undo = \"u\"\n").unwrap();
+        fs::write(&json_file, r#"{"undo": "u"}"#).unwrap();
+
+        let expected = BTreeMap::from([("undo".to_string(), "u".to_string())]);
+        assert_eq!(
+            read::<BTreeMap<String, String>>(&toml_file).unwrap(),
+            expected
+        );
+        assert_eq!(
+            read::<BTreeMap<String, String>>(&json_file).unwrap(),
+            expected
+        );
+        assert!(read::<BTreeMap<String, String>>(&dir.join("missing.toml")).is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
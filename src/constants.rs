@@ -1,12 +1,73 @@
-pub const TODO_LIST: char = 'a';
-pub const DOING_LIST: char = 's';
-pub const DONE_LIST: char = 'd';
-pub const MOVE_TO_TODO: char = 'A';
-pub const MOVE_TO_DOING: char = 'S';
-pub const MOVE_TO_DONE: char = 'D';
+use std::time::Duration;
+
+pub const FOCUS_LEFT: char = 'h';
+pub const FOCUS_RIGHT: char = 'l';
+pub const MOVE_TO_LEFT: char = 'H';
+pub const MOVE_TO_RIGHT: char = 'L';
+pub const NEW_COLUMN: char = 'c';
+pub const RENAME_COLUMN: char = 'r';
+pub const DELETE_COLUMN: char = 'C';
+pub const MOVE_COLUMN_LEFT: char = '<';
+pub const MOVE_COLUMN_RIGHT: char = '>';
 pub const DELETE_TASK: char = 'X';
+/// Takes the selected card off the board, into the archive
+pub const ARCHIVE_TASK: char = 'a';
+/// Opens the archive, where cards can be searched and restored
+pub const OPEN_ARCHIVE: char = 'A';
+pub const EDIT_TASK: char = 'e';
+pub const PRIORITY_UP: char = '+';
+pub const PRIORITY_DOWN: char = '-';
+/// Cycles the focused column through manual, priority, creation and due date order
+pub const SORT_COLUMN: char = 's';
+/// Sets or clears the WIP limit of the focused column
+pub const SET_LIMIT: char = 'm';
 pub const MOVE_UP: char = 'k';
 pub const MOVE_DOWN: char = 'j';
+pub const MOVE_TASK_UP: char = 'K';
+pub const MOVE_TASK_DOWN: char = 'J';
+/// Pressed twice, like in vim
+pub const SELECT_FIRST: char = 'g';
+pub const SELECT_LAST: char = 'G';
+pub const MOVE_TASK_TOP: char = 'T';
+pub const MOVE_TASK_BOTTOM: char = 'B';
 pub const CHANGE_INPUT_MODE: char = 'p';
 pub const EXIT: char = 'q';
+/// Used together with Ctrl while typing
+pub const DELETE_WORD: char = 'w';
+pub const SAVE: char = 'w';
+pub const UNDO: char = 'u';
+/// Used together with Ctrl
+pub const REDO: char = 'r';
+pub const BOARD_PICKER: char = 'b';
+pub const NEW_BOARD: char = 'c';
+pub const RENAME_BOARD: char = 'r';
+pub const DELETE_BOARD: char = 'X';
+pub const TAG_FILTER: char = 't';
+/// Opens the search prompt. Matches are highlighted while typing
+pub const SEARCH: char = '/';
+pub const NEXT_MATCH: char = 'n';
+pub const PREVIOUS_MATCH: char = 'N';
+/// Shows only the cards due by the end of the week, or everything again
+pub const DUE_FILTER: char = 'D';
+/// Opens the list of every key
+pub const HELP: char = '?';
+/// Answers of the confirmation popup. Enter also says yes and Esc says no
+pub const CONFIRM_YES: char = 'y';
+pub const CONFIRM_NO: char = 'n';
+/// Inside the tag filter, gives the selected tag the next color
+pub const TAG_COLOR: char = 'c';
 pub const HIGHLIGHT_SIMBOL: &str = "> ";
+/// Title given to columns stored without one
+pub const UNTITLED_COLUMN: &str = "Untitled";
+/// Put before the title of the focused column, so it shows without colors too
+pub const FOCUS_MARKER: &str = "▶ ";
+/// Time without changes before the board is saved on its own
+pub const AUTOSAVE_DELAY: Duration = Duration::from_secs(1);
+/// Cards due within this many days are highlighted as due soon
+pub const DUE_SOON_DAYS: i64 = 2;
+/// Time a message like "Saved" stays in the footer
+pub const TOAST_DURATION: Duration = Duration::from_secs(3);
+/// Longest time between two clicks on a card for them to be a double click
+pub const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+/// How long to wait for input before checking autosave and signals again
+pub const POLL_INTERVAL: Duration = Duration::from_millis(250);
@@ -0,0 +1,125 @@
+use color_eyre::{Result, eyre::eyre};
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+use crate::{helpers::xdg_dir, persistence::StorageKind};
+
+/// Environment variable that points to the data file when no flag is given
+pub const DATA_FILE_ENV: &str = "KANBAN_DATA_FILE";
+/// Name of the per-project data file, searched from the working directory upwards
+pub const PROJECT_FILE: &str = ".kanban.json";
+
+/// Finds where the workspace is stored. In order of preference: the `--data-file` flag, the
+/// `KANBAN_DATA_FILE` environment variable, a `.kanban.json` in the working directory or one of
+/// its parents, and finally `$XDG_DATA_HOME/kanban/kanban.json` (`kanban.db` for SQLite).
+///
+/// The project file is JSON, so it is skipped when another backend was asked for explicitly.
+pub fn resolve(flag: Option<PathBuf>, storage: Option<StorageKind>) -> Result<PathBuf> {
+    let env_file = env::var_os(DATA_FILE_ENV).map(PathBuf::from);
+    let cwd = env::current_dir()?;
+    let data_home = env::var_os("XDG_DATA_HOME").map(PathBuf::from);
+    let home = env::var_os("HOME").map(PathBuf::from);
+
+    resolve_from(flag, env_file, &cwd, data_home, home, storage)
+}
+
+/// Same as `resolve` but with every input given explicitly
+pub fn resolve_from(
+    flag: Option<PathBuf>,
+    env_file: Option<PathBuf>,
+    cwd: &Path,
+    data_home: Option<PathBuf>,
+    home: Option<PathBuf>,
+    storage: Option<StorageKind>,
+) -> Result<PathBuf> {
+    if let Some(path) = flag
+        .or(env_file)
+        .filter(|path| !path.as_os_str().is_empty())
+    {
+        return Ok(path);
+    }
+
+    if let Some(project_file) = cwd
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_FILE))
+        .find(|file| file.is_file())
+        .filter(|file| storage.is_none_or(|kind| kind == StorageKind::from_path(file)))
+    {
+        return Ok(project_file);
+    }
+
+    let data_home = xdg_dir(data_home, home, Path::new(".local").join("share"))
+        .ok_or_else(|| eyre!("Can't find a data directory, set {DATA_FILE_ENV}"))?;
+
+    let file_name = storage.unwrap_or_default().default_file_name();
+    Ok(data_home.join("kanban").join(file_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_flag_wins_over_env() {
+        let path = resolve_from(
+            Some(PathBuf::from("flag.json")),
+            Some(PathBuf::from("env.json")),
+            Path::new("/"),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(path, PathBuf::from("flag.json"));
+    }
+
+    #[test]
+    fn test_project_file_in_parent_directory() {
+        let project = env::temp_dir().join(format!("kanban-project-{}", std::process::id()));
+        let nested = project.join("src").join("widgets");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(project.join(PROJECT_FILE), "{}").unwrap();
+
+        let path = resolve_from(None, None, &nested, None, None, None).unwrap();
+        assert_eq!(path, project.join(PROJECT_FILE));
+
+        // An explicit SQLite backend can't open the JSON project file
+        let home = Some(PathBuf::from("/home/u"));
+        let sqlite = Some(StorageKind::Sqlite);
+        let path = resolve_from(None, None, &nested, None, home, sqlite).unwrap();
+        fs::remove_dir_all(&project).unwrap();
+        assert_eq!(path, PathBuf::from("/home/u/.local/share/kanban/kanban.db"));
+    }
+
+    #[test]
+    fn test_xdg_fallbacks() {
+        let cwd = Path::new("/");
+        let xdg = resolve_from(
+            None,
+            None,
+            cwd,
+            Some("/data".into()),
+            Some("/home/u".into()),
+            Some(StorageKind::Sqlite),
+        );
+        assert_eq!(xdg.unwrap(), PathBuf::from("/data/kanban/kanban.db"));
+
+        let relative = resolve_from(
+            None,
+            None,
+            cwd,
+            Some("data".into()),
+            Some("/home/u".into()),
+            None,
+        );
+        assert_eq!(
+            relative.unwrap(),
+            PathBuf::from("/home/u/.local/share/kanban/kanban.json")
+        );
+
+        assert!(resolve_from(None, None, cwd, None, None, None).is_err());
+    }
+}
@@ -0,0 +1,125 @@
+use chrono::{Datelike, Days, Local, NaiveDate, Weekday};
+use color_eyre::{Result, eyre::eyre};
+
+use crate::constants::DUE_SOON_DAYS;
+
+/// How close a due date is
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Urgency {
+    Overdue,
+    /// Due today or within `DUE_SOON_DAYS`
+    Soon,
+    Later,
+}
+
+/// Today in the local time zone. Due dates never look at anything but the local clock
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// Reads a due date relative to `today`: `2026-11-03`, `today`, `tomorrow`, a weekday such as
+/// `fri` or `friday` (the next one, never today) or an offset such as `+3d` or `+2w`
+pub fn parse_due(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let text = text.trim().to_lowercase();
+    if let Ok(date) = NaiveDate::parse_from_str(&text, "%Y-%m-%d") {
+        return Some(date);
+    }
+    match text.as_str() {
+        "today" => Some(today),
+        "tomorrow" => today.succ_opt(),
+        _ => offset(&text, today).or_else(|| weekday(&text, today)),
+    }
+}
+
+/// Same as `parse_due`, but an empty text clears the date and anything unknown is an error
+pub fn parse_optional_due(text: &str, today: NaiveDate) -> Result<Option<NaiveDate>> {
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    parse_due(text, today).map(Some).ok_or_else(|| {
+        eyre!(
+            "Unknown date '{}', try 2026-11-03, tomorrow, fri or +3d",
+            text.trim()
+        )
+    })
+}
+
+fn offset(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let text = text.strip_prefix('+')?;
+    let (amount, days) = match (text.strip_suffix('d'), text.strip_suffix('w')) {
+        (Some(amount), _) => (amount, 1),
+        (_, Some(amount)) => (amount, 7),
+        _ => return None,
+    };
+    let amount: u64 = amount.parse().ok()?;
+    today.checked_add_days(Days::new(amount.checked_mul(days)?))
+}
+
+fn weekday(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let target: Weekday = text.parse().ok()?;
+    let ahead = (target.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    let ahead = if ahead == 0 { 7 } else { ahead };
+    today.checked_add_days(Days::new(ahead.into()))
+}
+
+pub fn urgency(due: NaiveDate, today: NaiveDate) -> Urgency {
+    match (due - today).num_days() {
+        days if days < 0 => Urgency::Overdue,
+        days if days <= DUE_SOON_DAYS => Urgency::Soon,
+        _ => Urgency::Later,
+    }
+}
+
+/// Last day of the week `today` is in, weeks ending on Sunday
+pub fn end_of_week(today: NaiveDate) -> NaiveDate {
+    let left = 6 - today.weekday().num_days_from_monday();
+    today + Days::new(left.into())
+}
+
+/// Short form for the columns: the day and month, plus the year when it isn't this year
+pub fn format_due(due: NaiveDate, today: NaiveDate) -> String {
+    if due.year() == today.year() {
+        due.format("%b %d").to_string()
+    } else {
+        due.format("%Y-%m-%d").to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_parse_due() {
+        // A Sunday
+        let today = date("2026-10-18");
+        assert_eq!(parse_due("2026-11-03", today), Some(date("2026-11-03")));
+        assert_eq!(parse_due("Tomorrow", today), Some(date("2026-10-19")));
+        assert_eq!(parse_due("fri", today), Some(date("2026-10-23")));
+        assert_eq!(parse_due("sunday", today), Some(date("2026-10-25")));
+        assert_eq!(parse_due("+3d", today), Some(date("2026-10-21")));
+        assert_eq!(parse_due("+2w", today), Some(date("2026-11-01")));
+        assert_eq!(parse_due("soon", today), None);
+        assert_eq!(parse_due("+d", today), None);
+        assert_eq!(parse_due("+3é", today), None);
+        assert_eq!(parse_due("é", today), None);
+
+        assert_eq!(parse_optional_due(" ", today).unwrap(), None);
+        assert!(parse_optional_due("someday", today).is_err());
+    }
+
+    #[test]
+    fn test_urgency_and_week() {
+        let today = date("2026-10-14");
+        assert_eq!(urgency(date("2026-10-13"), today), Urgency::Overdue);
+        assert_eq!(urgency(today, today), Urgency::Soon);
+        assert_eq!(urgency(date("2026-10-20"), today), Urgency::Later);
+        assert_eq!(end_of_week(today), date("2026-10-18"));
+        assert_eq!(end_of_week(date("2026-10-18")), date("2026-10-18"));
+        assert_eq!(format_due(date("2027-01-02"), today), "2027-01-02");
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::BTreeSet;
+
+use chrono::NaiveDate;
+
+use crate::{due::end_of_week, task::Task};
+
+/// Which cards the columns show. The default filter shows every card
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Filter {
+    /// Cards carrying any of these tags are shown. Empty means no tag filter
+    pub tags: BTreeSet<String>,
+    /// Only cards due on this day or before are shown, overdue ones included
+    pub due_by: Option<NaiveDate>,
+}
+
+impl Filter {
+    pub fn matches(&self, task: &Task) -> bool {
+        let tagged = self.tags.is_empty() || task.tags.iter().any(|tag| self.tags.contains(tag));
+        let due = self
+            .due_by
+            .is_none_or(|by| task.due.is_some_and(|due| due <= by));
+        tagged && due
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.tags.is_empty() || self.due_by.is_some()
+    }
+
+    /// Shows only the cards due by the end of the week of `today`, or stops doing so
+    pub fn toggle_due_this_week(&mut self, today: NaiveDate) {
+        self.due_by = match self.due_by {
+            Some(_) => None,
+            None => Some(end_of_week(today)),
+        };
+    }
+
+    /// Adds `tag` to the filter, or removes it when it was already there
+    pub fn toggle_tag(&mut self, tag: &str) {
+        if !self.tags.remove(tag) {
+            self.tags.insert(tag.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_filter() {
+        let task = Task::from_input("Fix login #bug");
+        let mut filter = Filter::default();
+        assert!(filter.matches(&task));
+
+        filter.toggle_tag("docs");
+        assert!(!filter.matches(&task));
+        filter.toggle_tag("bug");
+        assert!(filter.matches(&task));
+
+        filter.toggle_tag("bug");
+        filter.toggle_tag("docs");
+        assert!(!filter.is_active());
+    }
+
+    #[test]
+    fn test_due_this_week_filter() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+        let mut filter = Filter::default();
+        filter.toggle_due_this_week(today);
+
+        let mut task = Task::new("Task 1".to_string());
+        assert!(!filter.matches(&task));
+        task.due = NaiveDate::from_ymd_opt(2026, 10, 18);
+        assert!(filter.matches(&task));
+        task.due = NaiveDate::from_ymd_opt(2026, 10, 1);
+        assert!(filter.matches(&task));
+        task.due = NaiveDate::from_ymd_opt(2026, 10, 19);
+        assert!(!filter.matches(&task));
+
+        filter.toggle_due_this_week(today);
+        assert!(!filter.is_active());
+    }
+}
@@ -1,4 +1,5 @@
 use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use std::path::{Path, PathBuf};
 
 /// helper function to create a centered rect using up certain percentage of the available rect `r`
 pub fn popup_area(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
@@ -8,3 +9,33 @@ pub fn popup_area(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
     let [area] = horizontal.areas(area);
     area
 }
+
+/// Base directory of the XDG spec: `xdg_home`, the value of a variable like `XDG_DATA_HOME`, or
+/// `fallback` inside the home directory when it is unset
+pub fn xdg_dir(
+    xdg_home: Option<PathBuf>,
+    home: Option<PathBuf>,
+    fallback: impl AsRef<Path>,
+) -> Option<PathBuf> {
+    // The XDG spec asks to ignore relative paths
+    xdg_home
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| home.map(|home| home.join(fallback)))
+}
+
+/// Removes from `input` the words `take` accepts, collapsing the spaces they leave behind.
+/// Line breaks are kept
+pub fn take_words(input: &str, mut take: impl FnMut(&str) -> bool) -> String {
+    input
+        .lines()
+        .map(|line| {
+            line.split_whitespace()
+                .filter(|word| !take(word))
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
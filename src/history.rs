@@ -0,0 +1,762 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use uuid::Uuid;
+
+use crate::{
+    archive::{Archive, ArchivedTask},
+    persistence::ColumnData,
+    task::Task,
+    widgets::kanban_column::{KanbanColumn, SortMode, loaded_title},
+};
+
+/// Number of changes kept for undo. Older ones are forgotten
+pub const HISTORY_LIMIT: usize = 100;
+
+/// Column and card that should get the focus after a command ran
+pub type Focus = (usize, Option<usize>);
+
+/// A reversible change to the columns or the archive of a board. Every mutation of the board goes
+/// through one of these so it can be undone and redone. Positions are indexes at the time of the
+/// change.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Command {
+    AddTask {
+        column: usize,
+        position: usize,
+        task: Task,
+    },
+    DeleteTask {
+        column: usize,
+        position: usize,
+        task: Task,
+    },
+    EditTask {
+        column: usize,
+        position: usize,
+        before: Task,
+        after: Task,
+    },
+    /// Takes the card at `position` off the board, into `archived` inside the archive
+    ArchiveTask {
+        column: usize,
+        position: usize,
+        archived: usize,
+        card: ArchivedTask,
+    },
+    /// Puts the card at `archived` in the archive back on the board
+    RestoreTask {
+        column: usize,
+        position: usize,
+        archived: usize,
+        card: ArchivedTask,
+    },
+    MoveTask {
+        /// Id of the moved card, used to list its activity
+        #[serde(default)]
+        task: Uuid,
+        from_column: usize,
+        from_position: usize,
+        to_column: usize,
+        to_position: usize,
+    },
+    AddColumn {
+        position: usize,
+        column: ColumnData,
+    },
+    DeleteColumn {
+        position: usize,
+        column: ColumnData,
+    },
+    RenameColumn {
+        position: usize,
+        before: String,
+        after: String,
+    },
+    SwapColumns {
+        first: usize,
+        second: usize,
+    },
+    SortColumn {
+        position: usize,
+        before: SortMode,
+        after: SortMode,
+    },
+    LimitColumn {
+        position: usize,
+        before: Option<usize>,
+        after: Option<usize>,
+    },
+}
+
+impl Command {
+    /// Runs the change on `columns` and `archive`
+    pub fn apply(&self, columns: &mut Vec<KanbanColumn>, archive: &mut Archive) -> Focus {
+        match self {
+            Command::AddTask {
+                column,
+                position,
+                task,
+            } => {
+                columns[*column].insert(*position, task.clone());
+                (*column, Some(*position))
+            }
+            Command::DeleteTask {
+                column, position, ..
+            } => {
+                columns[*column].remove(*position);
+                (*column, Some(*position))
+            }
+            Command::EditTask {
+                column,
+                position,
+                after,
+                ..
+            } => {
+                columns[*column].replace(*position, after.clone());
+                (*column, Some(*position))
+            }
+            Command::ArchiveTask {
+                column,
+                position,
+                archived,
+                card,
+            } => {
+                columns[*column].remove(*position);
+                archive.insert(*archived, card.clone());
+                (*column, Some(*position))
+            }
+            Command::RestoreTask {
+                column,
+                position,
+                archived,
+                ..
+            } => {
+                let mut task = archive.remove(*archived).task;
+                // Counts as entering the column, so auto-archive doesn't take it right back
+                task.moved_at = Some(Utc::now());
+                columns[*column].insert(*position, task);
+                (*column, Some(*position))
+            }
+            Command::MoveTask {
+                from_column,
+                from_position,
+                to_column,
+                to_position,
+                ..
+            } => {
+                let mut task = columns[*from_column].remove(*from_position);
+                if from_column != to_column {
+                    task.moved_at = Some(Utc::now());
+                }
+                columns[*to_column].insert(*to_position, task);
+                (*to_column, Some(*to_position))
+            }
+            Command::AddColumn { position, column } => {
+                columns.insert(*position, KanbanColumn::from(column.clone()));
+                (*position, Some(0))
+            }
+            Command::DeleteColumn { position, .. } => {
+                columns.remove(*position);
+                ((*position).min(columns.len() - 1), Some(0))
+            }
+            Command::RenameColumn {
+                position, after, ..
+            } => {
+                columns[*position].rename(loaded_title(after.clone()));
+                (*position, Some(0))
+            }
+            Command::SwapColumns { first, second } => {
+                columns.swap(*first, *second);
+                (*second, Some(0))
+            }
+            Command::SortColumn {
+                position, after, ..
+            } => {
+                columns[*position].set_sort(*after);
+                (*position, columns[*position].selected())
+            }
+            Command::LimitColumn {
+                position, after, ..
+            } => {
+                columns[*position].set_limit(*after);
+                (*position, columns[*position].selected())
+            }
+        }
+    }
+
+    /// Id of the card this command changes, `None` for column changes
+    pub fn task(&self) -> Option<Uuid> {
+        match self {
+            Command::AddTask { task, .. } | Command::DeleteTask { task, .. } => Some(task.id),
+            Command::EditTask { after, .. } => Some(after.id),
+            Command::ArchiveTask { card, .. } | Command::RestoreTask { card, .. } => {
+                Some(card.task.id)
+            }
+            Command::MoveTask { task, .. } => Some(*task),
+            _ => None,
+        }
+    }
+
+    /// One line summary of the change for the activity of a card
+    pub fn describe(&self, columns: &[KanbanColumn]) -> String {
+        let title = |i: &usize| columns.get(*i).map_or("a deleted column", |c| c.title());
+        match self {
+            Command::AddTask { column, .. } => format!("Added to {}", title(column)),
+            Command::DeleteTask { column, .. } => format!("Deleted from {}", title(column)),
+            Command::EditTask { before, after, .. } => {
+                let change = if before.title != after.title {
+                    "Title"
+                } else if before.priority != after.priority {
+                    "Priority"
+                } else if before.due != after.due {
+                    "Due date"
+                } else if before.tags != after.tags {
+                    "Tags"
+                } else {
+                    "Description"
+                };
+                format!("{change} changed")
+            }
+            Command::ArchiveTask { column, .. } => format!("Archived from {}", title(column)),
+            Command::RestoreTask { column, .. } => format!("Restored to {}", title(column)),
+            Command::MoveTask {
+                from_column,
+                to_column,
+                ..
+            } if from_column == to_column => format!("Reordered in {}", title(to_column)),
+            Command::MoveTask {
+                from_column,
+                to_column,
+                ..
+            } => format!("Moved from {} to {}", title(from_column), title(to_column)),
+            Command::AddColumn { column, .. } => format!("Column {} added", column.title),
+            Command::DeleteColumn { column, .. } => format!("Column {} deleted", column.title),
+            Command::RenameColumn { before, after, .. } => {
+                format!("Column {before} renamed to {after}")
+            }
+            Command::SwapColumns { .. } => "Columns reordered".to_string(),
+            Command::SortColumn {
+                position, after, ..
+            } => format!("{} sorted by {}", title(position), after.label()),
+            Command::LimitColumn {
+                position,
+                after: Some(limit),
+                ..
+            } => format!("{} limited to {limit} cards", title(position)),
+            Command::LimitColumn { position, .. } => format!("{} limit removed", title(position)),
+        }
+    }
+
+    /// The command that undoes this one
+    pub fn inverse(&self) -> Command {
+        match self.clone() {
+            Command::AddTask {
+                column,
+                position,
+                task,
+            } => Command::DeleteTask {
+                column,
+                position,
+                task,
+            },
+            Command::DeleteTask {
+                column,
+                position,
+                task,
+            } => Command::AddTask {
+                column,
+                position,
+                task,
+            },
+            Command::EditTask {
+                column,
+                position,
+                before,
+                after,
+            } => Command::EditTask {
+                column,
+                position,
+                before: after,
+                after: before,
+            },
+            Command::ArchiveTask {
+                column,
+                position,
+                archived,
+                card,
+            } => Command::RestoreTask {
+                column,
+                position,
+                archived,
+                card,
+            },
+            Command::RestoreTask {
+                column,
+                position,
+                archived,
+                card,
+            } => Command::ArchiveTask {
+                column,
+                position,
+                archived,
+                card,
+            },
+            Command::MoveTask {
+                task,
+                from_column,
+                from_position,
+                to_column,
+                to_position,
+            } => Command::MoveTask {
+                task,
+                from_column: to_column,
+                from_position: to_position,
+                to_column: from_column,
+                to_position: from_position,
+            },
+            Command::AddColumn { position, column } => Command::DeleteColumn { position, column },
+            Command::DeleteColumn { position, column } => Command::AddColumn { position, column },
+            Command::RenameColumn {
+                position,
+                before,
+                after,
+            } => Command::RenameColumn {
+                position,
+                before: after,
+                after: before,
+            },
+            Command::SwapColumns { first, second } => Command::SwapColumns {
+                first: second,
+                second: first,
+            },
+            Command::SortColumn {
+                position,
+                before,
+                after,
+            } => Command::SortColumn {
+                position,
+                before: after,
+                after: before,
+            },
+            Command::LimitColumn {
+                position,
+                before,
+                after,
+            } => Command::LimitColumn {
+                position,
+                before: after,
+                after: before,
+            },
+        }
+    }
+}
+
+/// A command together with the time it ran
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    #[serde(flatten)]
+    pub command: Command,
+    /// Histories saved before entries had a time get the time they were loaded
+    #[serde(default = "Utc::now")]
+    pub at: DateTime<Utc>,
+}
+
+impl Entry {
+    fn now(command: Command) -> Self {
+        Entry {
+            command,
+            at: Utc::now(),
+        }
+    }
+}
+
+/// Undo and redo stacks of a board. Saved together with the board so undo survives restarts
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct History {
+    undo: VecDeque<Entry>,
+    redo: Vec<Entry>,
+}
+
+impl History {
+    /// Runs `command` and remembers it. Anything that could be redone is dropped
+    pub fn execute(
+        &mut self,
+        command: Command,
+        columns: &mut Vec<KanbanColumn>,
+        archive: &mut Archive,
+    ) -> Focus {
+        let focus = command.apply(columns, archive);
+        self.undo.push_back(Entry::now(command));
+        if self.undo.len() > HISTORY_LIMIT {
+            self.undo.pop_front();
+        }
+        self.redo.clear();
+        focus
+    }
+
+    /// Reverts the last command. `None` when there is nothing to undo
+    pub fn undo(
+        &mut self,
+        columns: &mut Vec<KanbanColumn>,
+        archive: &mut Archive,
+    ) -> Option<Focus> {
+        let entry = self.undo.pop_back()?;
+        let focus = entry.command.inverse().apply(columns, archive);
+        self.redo.push(entry);
+        Some(focus)
+    }
+
+    /// Runs again the last undone command. `None` when there is nothing to redo
+    pub fn redo(
+        &mut self,
+        columns: &mut Vec<KanbanColumn>,
+        archive: &mut Archive,
+    ) -> Option<Focus> {
+        let entry = self.redo.pop()?;
+        let focus = entry.command.apply(columns, archive);
+        self.undo.push_back(Entry::now(entry.command));
+        Some(focus)
+    }
+
+    /// Changes made to the card `id` that can still be undone, oldest first
+    pub fn activity(&self, id: Uuid) -> impl Iterator<Item = &Entry> {
+        self.undo
+            .iter()
+            .filter(move |entry| entry.command.task() == Some(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::priority::Priority;
+    use chrono::NaiveDate;
+
+    fn titles(columns: &[KanbanColumn]) -> Vec<Vec<String>> {
+        columns
+            .iter()
+            .map(|c| c.to_json().into_iter().map(|t| t.title).collect())
+            .collect()
+    }
+
+    fn board() -> Vec<KanbanColumn> {
+        ["TODO", "Done"]
+            .into_iter()
+            .map(|title| KanbanColumn::new(title.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_undo_and_redo() {
+        let mut columns = board();
+        let mut archive = Archive::default();
+        let mut history = History::default();
+        let task = Task::new("Task 1".to_string());
+
+        history.execute(
+            Command::AddTask {
+                column: 0,
+                position: 0,
+                task,
+            },
+            &mut columns,
+            &mut archive,
+        );
+        let focus = history.execute(
+            Command::MoveTask {
+                task: Uuid::nil(),
+                from_column: 0,
+                from_position: 0,
+                to_column: 1,
+                to_position: 0,
+            },
+            &mut columns,
+            &mut archive,
+        );
+        assert_eq!(focus, (1, Some(0)));
+        assert_eq!(titles(&columns), [vec![], vec!["Task 1"]]);
+
+        assert_eq!(history.undo(&mut columns, &mut archive), Some((0, Some(0))));
+        assert_eq!(titles(&columns), [vec!["Task 1"], vec![]]);
+        history.undo(&mut columns, &mut archive);
+        assert_eq!(titles(&columns), [Vec::<String>::new(), vec![]]);
+        assert_eq!(history.undo(&mut columns, &mut archive), None);
+
+        history.redo(&mut columns, &mut archive);
+        history.redo(&mut columns, &mut archive);
+        assert_eq!(titles(&columns), [vec![], vec!["Task 1"]]);
+        assert_eq!(history.redo(&mut columns, &mut archive), None);
+    }
+
+    #[test]
+    fn test_reorder_inside_a_column() {
+        let mut columns = board();
+        let mut archive = Archive::default();
+        for i in 1..=3 {
+            columns[0].push(Task::new(format!("Task {i}")));
+        }
+        let mut history = History::default();
+
+        let focus = history.execute(
+            Command::MoveTask {
+                task: Uuid::nil(),
+                from_column: 0,
+                from_position: 2,
+                to_column: 0,
+                to_position: 0,
+            },
+            &mut columns,
+            &mut archive,
+        );
+        assert_eq!(focus, (0, Some(0)));
+        assert_eq!(titles(&columns)[0], ["Task 3", "Task 1", "Task 2"]);
+
+        assert_eq!(history.undo(&mut columns, &mut archive), Some((0, Some(2))));
+        assert_eq!(titles(&columns)[0], ["Task 1", "Task 2", "Task 3"]);
+    }
+
+    #[test]
+    fn test_activity_of_a_card() {
+        let mut columns = board();
+        let mut archive = Archive::default();
+        let mut history = History::default();
+        let task = Task::new("Task 1".to_string());
+        let id = task.id;
+
+        history.execute(
+            Command::AddTask {
+                column: 0,
+                position: 0,
+                task,
+            },
+            &mut columns,
+            &mut archive,
+        );
+        history.execute(
+            Command::AddTask {
+                column: 0,
+                position: 1,
+                task: Task::new("Task 2".to_string()),
+            },
+            &mut columns,
+            &mut archive,
+        );
+        history.execute(
+            Command::MoveTask {
+                task: id,
+                from_column: 0,
+                from_position: 0,
+                to_column: 1,
+                to_position: 0,
+            },
+            &mut columns,
+            &mut archive,
+        );
+
+        let activity: Vec<_> = history
+            .activity(id)
+            .map(|entry| entry.command.describe(&columns))
+            .collect();
+        assert_eq!(activity, ["Added to TODO", "Moved from TODO to Done"]);
+    }
+
+    #[test]
+    fn test_edit_names_the_changed_field() {
+        let describe = |edit: fn(&mut Task)| {
+            let before = Task::new("Task 1".to_string());
+            let mut after = before.clone();
+            edit(&mut after);
+            Command::EditTask {
+                column: 0,
+                position: 0,
+                before,
+                after,
+            }
+            .describe(&board())
+        };
+
+        assert_eq!(
+            describe(|t| t.title = "Task 2".to_string()),
+            "Title changed"
+        );
+        assert_eq!(
+            describe(|t| t.priority = Some(Priority::P1)),
+            "Priority changed"
+        );
+        assert_eq!(
+            describe(|t| t.due = NaiveDate::from_ymd_opt(2025, 1, 31)),
+            "Due date changed"
+        );
+        assert_eq!(
+            describe(|t| t.tags = vec!["bug".to_string()]),
+            "Tags changed"
+        );
+        assert_eq!(
+            describe(|t| t.description = "Notes".to_string()),
+            "Description changed"
+        );
+    }
+
+    #[test]
+    fn test_archive_and_restore() {
+        let mut columns = board();
+        let mut archive = Archive::default();
+        let task = Task::new("Task 1".to_string());
+        columns[1].push(task.clone());
+        let mut history = History::default();
+
+        history.execute(
+            Command::ArchiveTask {
+                column: 1,
+                position: 0,
+                archived: 0,
+                card: ArchivedTask::new(task, "Done"),
+            },
+            &mut columns,
+            &mut archive,
+        );
+        assert_eq!(titles(&columns)[1], Vec::<String>::new());
+        assert_eq!(archive.len(), 1);
+
+        history.undo(&mut columns, &mut archive);
+        assert_eq!(titles(&columns)[1], ["Task 1"]);
+        assert!(archive.is_empty());
+
+        history.redo(&mut columns, &mut archive);
+        let card = archive.get(0).unwrap().clone();
+        history.execute(
+            Command::RestoreTask {
+                column: 0,
+                position: 0,
+                archived: 0,
+                card,
+            },
+            &mut columns,
+            &mut archive,
+        );
+        assert_eq!(titles(&columns)[0], ["Task 1"]);
+        assert!(archive.is_empty());
+        assert_eq!(
+            history.undo.back().unwrap().command.describe(&columns),
+            "Restored to TODO"
+        );
+    }
+
+    #[test]
+    fn test_entries_without_time_still_load() {
+        let json = r#"{"undo": [{"type": "SwapColumns", "first": 0, "second": 1}], "redo": []}"#;
+        let history: History = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            history.undo[0].command,
+            Command::SwapColumns {
+                first: 0,
+                second: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_column_commands_are_reversible() {
+        let mut columns = board();
+        let mut archive = Archive::default();
+        let mut history = History::default();
+        let review = ColumnData {
+            title: "Review".to_string(),
+            tasks: vec![Task::new("Task 1".to_string())],
+            sort: SortMode::default(),
+            limit: None,
+        };
+
+        history.execute(
+            Command::AddColumn {
+                position: 1,
+                column: review.clone(),
+            },
+            &mut columns,
+            &mut archive,
+        );
+        history.execute(
+            Command::SwapColumns {
+                first: 1,
+                second: 2,
+            },
+            &mut columns,
+            &mut archive,
+        );
+        history.execute(
+            Command::DeleteColumn {
+                position: 2,
+                column: review,
+            },
+            &mut columns,
+            &mut archive,
+        );
+        assert_eq!(columns.len(), 2);
+
+        history.undo(&mut columns, &mut archive);
+        history.undo(&mut columns, &mut archive);
+        let names: Vec<_> = columns.iter().map(|c| c.title().to_string()).collect();
+        assert_eq!(names, ["TODO", "Review", "Done"]);
+        assert_eq!(titles(&columns)[1], ["Task 1"]);
+    }
+
+    #[test]
+    fn test_limit_is_reversible() {
+        let mut columns = board();
+        let mut archive = Archive::default();
+        let mut history = History::default();
+        history.execute(
+            Command::LimitColumn {
+                position: 0,
+                before: None,
+                after: Some(3),
+            },
+            &mut columns,
+            &mut archive,
+        );
+        assert_eq!(columns[0].limit(), Some(3));
+        assert_eq!(
+            history.undo[0].command.describe(&columns),
+            "TODO limited to 3 cards"
+        );
+
+        history.undo(&mut columns, &mut archive);
+        assert_eq!(columns[0].limit(), None);
+        history.redo(&mut columns, &mut archive);
+        assert_eq!(columns[0].limit(), Some(3));
+    }
+
+    #[test]
+    fn test_history_is_bounded_and_new_changes_drop_redo() {
+        let mut columns = board();
+        let mut archive = Archive::default();
+        let mut history = History::default();
+        for i in 0..HISTORY_LIMIT + 5 {
+            history.execute(
+                Command::AddTask {
+                    column: 0,
+                    position: 0,
+                    task: Task::new(format!("Task {i}")),
+                },
+                &mut columns,
+                &mut archive,
+            );
+        }
+        assert_eq!(history.undo.len(), HISTORY_LIMIT);
+
+        history.undo(&mut columns, &mut archive);
+        assert_eq!(history.redo.len(), 1);
+        history.execute(
+            Command::DeleteTask {
+                column: 0,
+                position: 0,
+                task: columns[0].to_json()[0].clone(),
+            },
+            &mut columns,
+            &mut archive,
+        );
+        assert!(history.redo.is_empty());
+    }
+}
@@ -1,92 +1,382 @@
 use crate::{
+    archive::{Archive, ArchivedTask, AutoArchive},
     constants::{
-        CHANGE_INPUT_MODE, DELETE_TASK, DOING_LIST, DONE_LIST, EXIT, MOVE_DOWN, MOVE_TO_DOING,
-        MOVE_TO_DONE, MOVE_TO_TODO, MOVE_UP, TODO_LIST,
+        AUTOSAVE_DELAY, BOARD_PICKER, CONFIRM_NO, CONFIRM_YES, DELETE_BOARD, DELETE_WORD,
+        DOUBLE_CLICK, EDIT_TASK, EXIT, HELP, MOVE_DOWN, MOVE_UP, NEW_BOARD, POLL_INTERVAL,
+        RENAME_BOARD, SEARCH, TAG_COLOR, TAG_FILTER, TOAST_DURATION,
     },
+    due::{parse_optional_due, today},
+    filter::Filter,
     helpers::popup_area,
-    persistence::Persistence,
-    widgets::{footer::Footer, input_box::InputBox, kanban_column::KanbanColumn},
+    history::{Command, Focus, History},
+    keymap::{Action, Chord, Keymap, Lookup},
+    persistence::{BoardData, ColumnData, DEFAULT_BOARD, Storage},
+    priority::Priority,
+    search::Search,
+    tags::{Palette, join_tags, parse_tags},
+    task::Task,
+    theme::Theme,
+    widgets::{
+        archive_view::ArchiveView,
+        board_picker::BoardPicker,
+        card_detail::{CardDetail, DetailField},
+        confirm::Confirm,
+        footer::{Footer, Status},
+        help::{HelpPopup, KeyHelp},
+        input_box::InputBox,
+        kanban_column::{KanbanColumn, SortMode},
+        tag_picker::TagPicker,
+    },
+    wip::WipPolicy,
 };
+use chrono::Utc;
 use color_eyre::Result;
 use ratatui::{
     DefaultTerminal, Frame,
-    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
+    crossterm::event::{
+        self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent,
+        MouseEventKind,
+    },
     layout::{Constraint, Layout, Position},
 };
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Instant,
+};
 
 /// Kanban app main struct. Used to manage user input (editing columns) and app renderization.
 pub struct Kanban {
     /// Flag to gracefully shutdown
     should_exit: bool,
-    /// Kanban List to render
-    todo_list: KanbanColumn,
-    doing_list: KanbanColumn,
-    done_list: KanbanColumn,
-    selected_column: SelectedColumn,
+    /// Set once saving on exit failed, so a second exit quits without saving
+    force_exit: bool,
+    /// Last message and when it was given. Shown in the footer until the next key press,
+    /// toasts also go away after `TOAST_DURATION`
+    status: Option<(Status, Instant)>,
+    /// Time of the first change not saved yet. Autosave kicks in `AUTOSAVE_DELAY` after it
+    dirty_since: Option<Instant>,
+    /// Name of the board being shown. Used as key when saving it
+    board_name: String,
+    storage: Box<dyn Storage>,
+    /// Kanban columns to render, from left to right
+    columns: Vec<KanbanColumn>,
+    /// Index inside `columns` of the focused column
+    selected_column: usize,
+    /// Undo/redo stacks of the board being shown
+    history: History,
+    /// Colors of the tags of the board being shown
+    palette: Palette,
+    /// Cards shown in the columns
+    filter: Filter,
+    /// Cards highlighted in the columns and visited with n/N
+    search: Option<Search>,
+    /// What to do when a card would take a column over its WIP limit
+    wip_policy: WipPolicy,
+    keymap: Keymap,
+    theme: Theme,
+    /// Keys typed so far of a sequence like `gg`
+    pending_keys: Vec<Chord>,
     input_mode: InputMode,
+    /// Mode to go back to once the input popup closes
+    previous_mode: InputMode,
     input_box: InputBox,
+    /// What the text written in the input box will be used for
+    input_target: InputTarget,
+    board_picker: BoardPicker,
+    /// Field selected in the card detail popup
+    detail_field: DetailField,
+    tag_picker: TagPicker,
+    help: HelpPopup,
+    /// Change waiting for a yes in the confirmation popup, with the question asked
+    confirmation: Option<(Deletion, String)>,
+    /// Column, card and time of the last click on a card, to tell double clicks apart
+    last_click: Option<(usize, usize, Instant)>,
+    /// Column and card held down with the mouse. They are moved where the button is released
+    dragging: Option<(usize, usize)>,
+    /// Cards taken off the board being shown
+    archive: Archive,
+    auto_archive: AutoArchive,
+    archive_view: ArchiveView,
 }
 
-/// Indicates the mode the user is in. Editing for adding task, Normal to move them.
-#[derive(Debug, PartialEq)]
+/// Indicates the mode the user is in. Editing for adding task, Normal to move them,
+/// BoardPicker while choosing which board to show, Detail while looking at a single card,
+/// TagPicker while choosing the tags to filter by, Help while reading the list of keys,
+/// Confirm while answering a yes/no question, Archive while browsing the archived cards.
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum InputMode {
     Normal,
     Editing,
+    BoardPicker,
+    Detail,
+    TagPicker,
+    Help,
+    Confirm,
+    Archive,
 }
 
-/// Helper enum used inside the Kanban logic. Helps with the selection/editing of columns
+/// Deletions asked for confirmation before they run
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Deletion {
+    Task,
+    Column,
+    /// The board selected in the board picker
+    Board,
+}
+
+/// Helper enum used inside the Kanban logic. Tells what to do with the submitted input
 #[derive(Debug, PartialEq)]
-pub enum SelectedColumn {
-    Todo,
-    Doing,
-    Done,
+enum InputTarget {
+    NewTask,
+    EditTask(DetailField),
+    NewColumn,
+    RenameColumn,
+    ColumnLimit,
+    Search,
+    /// Narrows down the cards listed in the archive
+    ArchiveSearch,
+    NewBoard,
+    RenameBoard,
 }
 
 impl Kanban {
-    pub fn new() -> Result<Self> {
-        let (todo_list, doing_list, done_list) = Persistence::load()?;
-        Ok(Kanban {
+    /// Opens `board`, or the first board of the workspace when none is given. Boards that
+    /// don't exist yet are created with the default columns.
+    pub fn new(
+        storage: Box<dyn Storage>,
+        board: Option<String>,
+        wip_policy: WipPolicy,
+        keymap: Keymap,
+        theme: Theme,
+        auto_archive: AutoArchive,
+    ) -> Result<Self> {
+        let board_name = match board {
+            Some(board) => board,
+            None => storage
+                .board_names()?
+                .into_iter()
+                .next()
+                .unwrap_or_else(|| DEFAULT_BOARD.to_string()),
+        };
+        let (columns, history, palette, archive) = load_board(storage.as_ref(), &board_name)?;
+        let mut kanban = Kanban {
             should_exit: false,
-            todo_list,
-            doing_list,
-            done_list,
-            selected_column: SelectedColumn::Todo,
+            force_exit: false,
+            status: None,
+            dirty_since: None,
+            board_name,
+            storage,
+            columns,
+            selected_column: 0,
+            history,
+            palette,
+            filter: Filter::default(),
+            search: None,
+            wip_policy,
+            keymap,
+            theme,
+            pending_keys: Vec::new(),
             input_mode: InputMode::Normal,
+            previous_mode: InputMode::Normal,
             input_box: InputBox::default(),
-        })
+            input_target: InputTarget::NewTask,
+            board_picker: BoardPicker::default(),
+            detail_field: DetailField::default(),
+            tag_picker: TagPicker::default(),
+            help: HelpPopup::default(),
+            confirmation: None,
+            last_click: None,
+            dragging: None,
+            archive,
+            auto_archive,
+            archive_view: ArchiveView::default(),
+        };
+        kanban.archive_done_cards();
+        Ok(kanban)
     }
 
     /// Main ratatui loop. Draw, ask for input and repeat.
+    ///
+    /// The board is flushed to disk before leaving, also when a termination signal arrives or
+    /// the loop panics. The terminal itself is restored by the caller and the ratatui panic hook.
     pub fn run(mut self, mut terminal: DefaultTerminal) -> Result<()> {
+        let terminate = Arc::new(AtomicBool::new(false));
+        register_signals(&terminate)?;
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            self.event_loop(&mut terminal, &terminate)
+        }));
+        match result {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(error)) => {
+                // The terminal is usually gone, e.g. after SIGHUP. Keep the edits anyway
+                let _ = self.save();
+                Err(error)
+            }
+            Err(payload) => {
+                // Best effort, the board may be half way through a change
+                let _ = self.save();
+                panic::resume_unwind(payload)
+            }
+        }
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal, terminate: &AtomicBool) -> Result<()> {
         while !self.should_exit {
+            // Checked before touching the terminal, which may be gone after a signal
+            if terminate.load(Ordering::Relaxed) {
+                // There is no one left to read an error, leave anyway
+                self.force_exit = true;
+                self.handle_exit();
+                break;
+            }
             terminal.draw(|frame| self.render(frame))?;
-            if let Event::Key(key) = event::read()? {
-                self.handle_key(key);
-            };
+            // Wake up from time to time to autosave and check for signals
+            if event::poll(POLL_INTERVAL)? {
+                match event::read()? {
+                    Event::Key(key) => self.handle_key(key),
+                    Event::Mouse(mouse) => self.handle_mouse(mouse),
+                    _ => {}
+                }
+            }
+            self.autosave();
+            self.expire_status();
         }
         Ok(())
     }
 
+    /// Saves the board once it has been left untouched for `AUTOSAVE_DELAY`
+    fn autosave(&mut self) {
+        if self
+            .dirty_since
+            .is_some_and(|since| since.elapsed() >= AUTOSAVE_DELAY)
+        {
+            let saved = self.save();
+            if self.report(saved).is_none() {
+                // Don't retry on every tick, wait for the next change or delay
+                self.dirty_since = Some(Instant::now());
+            }
+        }
+    }
+
+    /// Writes the current board to disk
+    fn save(&mut self) -> Result<()> {
+        let board = BoardData::new(
+            &self.board_name,
+            &self.columns,
+            &self.history,
+            &self.palette,
+            &self.archive,
+        );
+        self.storage.save_board(&board)?;
+        self.dirty_since = None;
+        Ok(())
+    }
+
+    /// Records that the board changed and must be saved
+    fn mark_dirty(&mut self) {
+        self.dirty_since = Some(Instant::now());
+    }
+
     /// Private method used to assing every Widget their available screen and tell them to render.
     fn render(&mut self, frame: &mut Frame) {
         let layout = Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]);
         let [main_area, footer_area] = layout.areas(frame.area());
 
-        let [todo_area, doing_area, done_area] = Layout::horizontal([
-            Constraint::Fill(1),
-            Constraint::Fill(1),
-            Constraint::Fill(1),
-        ])
-        .areas(main_area);
+        let column_areas =
+            Layout::horizontal(vec![Constraint::Fill(1); self.columns.len()]).split(main_area);
 
-        frame.render_widget(Footer, footer_area);
+        frame.render_widget(
+            Footer::new(
+                self.status.as_ref().map(|(status, _)| status),
+                self.hints(),
+                &self.theme,
+            ),
+            footer_area,
+        );
 
-        frame.render_widget(&mut self.todo_list, todo_area);
-        frame.render_widget(&mut self.doing_list, doing_area);
-        frame.render_widget(&mut self.done_list, done_area);
+        for (i, (column, area)) in self.columns.iter_mut().zip(column_areas.iter()).enumerate() {
+            column.set_filter(&self.filter);
+            column.render(
+                *area,
+                frame.buffer_mut(),
+                &self.palette,
+                self.search.as_ref(),
+                &self.theme,
+                i == self.selected_column,
+            );
+        }
 
-        if self.input_mode == InputMode::Editing {
-            self.render_input_widget(frame, main_area);
+        match self.input_mode {
+            InputMode::Normal => {}
+            InputMode::Editing => {
+                // Fields of a card are edited on top of its detail
+                match self.previous_mode {
+                    InputMode::Detail => self.render_detail(frame, main_area),
+                    InputMode::Archive => self.render_archive(frame, main_area),
+                    _ => {}
+                }
+                self.render_input_widget(frame, main_area)
+            }
+            InputMode::BoardPicker => {
+                let area = popup_area(main_area, 40, 50);
+                self.board_picker
+                    .render(area, frame.buffer_mut(), &self.theme)
+            }
+            InputMode::Detail => self.render_detail(frame, main_area),
+            InputMode::Archive => self.render_archive(frame, main_area),
+            InputMode::Confirm => {
+                // Boards are deleted from the board picker, keep it behind the question
+                if self.previous_mode == InputMode::BoardPicker {
+                    let area = popup_area(main_area, 40, 50);
+                    self.board_picker
+                        .render(area, frame.buffer_mut(), &self.theme);
+                }
+                if let Some((_, question)) = &self.confirmation {
+                    frame.render_widget(
+                        Confirm::new(question, &self.theme),
+                        popup_area(main_area, 40, 20),
+                    );
+                }
+            }
+            InputMode::Help => {
+                let area = popup_area(main_area, 60, 80);
+                self.help
+                    .render(area, frame.buffer_mut(), &self.keymap, &self.theme)
+            }
+            InputMode::TagPicker => {
+                let area = popup_area(main_area, 40, 50);
+                self.tag_picker
+                    .render(area, frame.buffer_mut(), &self.theme)
+            }
+        }
+    }
+
+    fn render_archive(&mut self, frame: &mut Frame, main_area: ratatui::prelude::Rect) {
+        let area = popup_area(main_area, 60, 70);
+        self.archive_view
+            .render(area, frame.buffer_mut(), &self.archive, &self.theme)
+    }
+
+    fn render_detail(&self, frame: &mut Frame, main_area: ratatui::prelude::Rect) {
+        if let Some((_, task)) = self.selected_task() {
+            let activity = self
+                .history
+                .activity(task.id)
+                .map(|entry| (entry.at, entry.command.describe(&self.columns)))
+                .collect();
+            let detail = CardDetail::new(
+                task,
+                &self.palette,
+                &self.theme,
+                self.detail_field,
+                activity,
+            );
+            frame.render_widget(detail, popup_area(main_area, 60, 70));
         }
     }
 
@@ -94,117 +384,1312 @@ impl Kanban {
     /// widget to render
     fn render_input_widget(&mut self, frame: &mut Frame<'_>, main_area: ratatui::prelude::Rect) {
         let input_area = popup_area(main_area, 60, 20);
-        frame.render_widget(&mut self.input_box, input_area);
+        self.input_box
+            .render(input_area, frame.buffer_mut(), &self.theme);
+        // The input box knows how the text wrapped, so it tells where the cursor goes
+        frame.set_cursor_position(self.input_box.cursor_position(input_area));
+    }
 
-        // TODO: Intentar delegar el renderizado del cursor al input box
-        frame.set_cursor_position(Position::new(
-            // Draw the cursor at the current position in the input field.
-            // This position is can be controlled via the left and right arrow key
-            input_area.x + self.input_box.get_char_index() as u16 + 1,
-            // Move one line down, from the border to the input line
-            input_area.y + 1,
-        ));
+    /// Clicks focus a column and select the card under the pointer, a double click edits it and
+    /// dragging moves it. The wheel moves the selection of the column under the pointer,
+    /// scrolling it. The mouse only works on the board, popups are left to the keys
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        if self.input_mode != InputMode::Normal {
+            return;
+        }
+        let position = Position::new(mouse.column, mouse.row);
+        let dragging = match mouse.kind {
+            MouseEventKind::Up(MouseButton::Left) => self.dragging.take(),
+            _ => None,
+        };
+        let Some(column) = self.columns.iter().position(|c| c.contains(position)) else {
+            return;
+        };
+        let card = self.columns[column].card_at(position);
+
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.status = None;
+                self.pending_keys.clear();
+                self.dragging = None;
+                self.change_focus(column);
+                let Some(card) = card else {
+                    return;
+                };
+                self.current_column().select(Some(card));
+                let double_click = self.last_click.is_some_and(|(c, i, at)| {
+                    (c, i) == (column, card) && at.elapsed() <= DOUBLE_CLICK
+                });
+                if double_click {
+                    self.last_click = None;
+                    self.open_input(InputTarget::EditTask(DetailField::Title));
+                } else {
+                    self.last_click = Some((column, card, Instant::now()));
+                    self.dragging = Some((column, card));
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                let Some((from, dragged)) = dragging else {
+                    return;
+                };
+                if from == column {
+                    // Released on the card itself or the border, nothing to move
+                    if let Some(card) = card.filter(|card| *card != dragged) {
+                        self.reorder_item(card);
+                    }
+                } else {
+                    let position = card.unwrap_or(self.columns[column].len());
+                    if let Some(focus) = self.move_item_to(column, position) {
+                        self.focus(focus);
+                    }
+                }
+            }
+            MouseEventKind::ScrollDown => {
+                self.change_focus(column);
+                self.current_column().select_next();
+            }
+            MouseEventKind::ScrollUp => {
+                self.change_focus(column);
+                self.current_column().select_previous();
+            }
+            _ => {}
+        }
     }
 
     fn handle_key(&mut self, key: KeyEvent) {
         if key.kind != KeyEventKind::Press {
             return;
         }
+        self.status = None;
 
         match self.input_mode {
             InputMode::Normal => self.normal_mode_input(key),
             InputMode::Editing => self.editing_mode_input(key),
+            InputMode::BoardPicker => self.board_picker_input(key),
+            InputMode::Detail => self.detail_mode_input(key),
+            InputMode::TagPicker => self.tag_picker_input(key),
+            InputMode::Help => self.help_mode_input(key),
+            InputMode::Confirm => self.confirm_mode_input(key),
+            InputMode::Archive => self.archive_mode_input(key),
         }
     }
 
     fn normal_mode_input(&mut self, key: KeyEvent) {
+        self.pending_keys.push(Chord::from(key));
+        let action = match self.keymap.lookup(&self.pending_keys) {
+            Lookup::Action(action) => action,
+            Lookup::Pending => return,
+            Lookup::Unbound => {
+                let keys = std::mem::take(&mut self.pending_keys);
+                // Columns are numbered from 1 on screen
+                if let [chord] = keys.as_slice()
+                    && chord.modifiers.is_empty()
+                    && let KeyCode::Char(c @ '1'..='9') = chord.code
+                {
+                    self.change_focus(c as usize - '1' as usize);
+                }
+                return;
+            }
+        };
+        self.pending_keys.clear();
+
+        match action {
+            Action::Back if self.search.is_some() => self.search = None,
+            Action::Exit | Action::Back => self.handle_exit(),
+            Action::Save => {
+                let saved = self.save();
+                if self.report(saved).is_some() {
+                    self.notify("Saved".to_string());
+                }
+            }
+            Action::Undo => self.undo(),
+            Action::Redo => self.redo(),
+            Action::MoveDown => self.current_column().select_next(),
+            Action::MoveUp => self.current_column().select_previous(),
+            Action::SelectFirst => self.current_column().select_first(),
+            Action::SelectLast => self.current_column().select_last(),
+            Action::FocusLeft => self.change_focus(self.selected_column.saturating_sub(1)),
+            Action::FocusRight => self.change_focus(self.selected_column + 1),
+            Action::NewTask => self.open_input(InputTarget::NewTask),
+            Action::EditTask => self.open_input(InputTarget::EditTask(DetailField::Title)),
+            Action::OpenDetail => self.open_detail(),
+            Action::DeleteTask => {
+                if let Some((_, task)) = self.selected_task() {
+                    let question = format!("Delete '{}'?", short_title(task));
+                    self.ask(Deletion::Task, question);
+                }
+            }
+            Action::MoveToLeft => {
+                if let Some(destination) = self.selected_column.checked_sub(1) {
+                    self.move_item(destination);
+                }
+            }
+            Action::MoveToRight => self.move_item(self.selected_column + 1),
+            Action::MoveTaskUp => self.reorder_to_row(|row| row.saturating_sub(1)),
+            Action::MoveTaskDown => self.reorder_to_row(|row| row + 1),
+            Action::MoveTaskTop => self.reorder_to_row(|_| 0),
+            Action::MoveTaskBottom => self.reorder_to_row(|_| usize::MAX),
+            Action::PriorityUp => self.change_priority(Priority::raise),
+            Action::PriorityDown => self.change_priority(Priority::lower),
+            Action::SortColumn => self.sort_column(),
+            Action::SetLimit => self.open_input(InputTarget::ColumnLimit),
+            Action::NewColumn => self.open_input(InputTarget::NewColumn),
+            Action::RenameColumn => self.open_input(InputTarget::RenameColumn),
+            Action::DeleteColumn => {
+                // The board always keeps one column
+                if self.columns.len() > 1 {
+                    let column = &self.columns[self.selected_column];
+                    let question = format!(
+                        "Delete the column '{}' and its {} cards?",
+                        column.title(),
+                        column.len()
+                    );
+                    self.ask(Deletion::Column, question);
+                }
+            }
+            Action::MoveColumnLeft => {
+                if let Some(destination) = self.selected_column.checked_sub(1) {
+                    self.swap_column(destination);
+                }
+            }
+            Action::MoveColumnRight => self.swap_column(self.selected_column + 1),
+            Action::ArchiveTask => self.archive_item(),
+            Action::OpenArchive => self.open_archive(),
+            Action::BoardPicker => self.open_board_picker(),
+            Action::TagFilter => self.open_tag_picker(),
+            Action::DueFilter => self.filter.toggle_due_this_week(today()),
+            Action::Search => self.open_input(InputTarget::Search),
+            Action::NextMatch => self.jump_to_match(true),
+            Action::PreviousMatch => self.jump_to_match(false),
+            Action::Help => {
+                self.help.reset();
+                self.input_mode = InputMode::Help;
+            }
+        }
+    }
+
+    /// Opens the confirmation popup. `confirmation` only runs if the answer is yes
+    fn ask(&mut self, confirmation: Deletion, question: String) {
+        self.confirmation = Some((confirmation, question));
+        self.previous_mode = self.input_mode;
+        self.input_mode = InputMode::Confirm;
+    }
+
+    fn confirm_mode_input(&mut self, key: KeyEvent) {
+        let confirmed = match key.code {
+            KeyCode::Char(CONFIRM_YES) | KeyCode::Enter => true,
+            KeyCode::Char(CONFIRM_NO) | KeyCode::Char(EXIT) | KeyCode::Esc => false,
+            _ => return,
+        };
+        self.input_mode = self.previous_mode;
+        let Some((confirmation, _)) = self.confirmation.take() else {
+            return;
+        };
+        if confirmed {
+            match confirmation {
+                Deletion::Task => self.delete_item(),
+                Deletion::Column => self.delete_column(),
+                Deletion::Board => self.delete_board(),
+            }
+        }
+    }
+
+    fn help_mode_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char(EXIT) | KeyCode::Char(HELP) | KeyCode::Esc => {
+                self.input_mode = InputMode::Normal
+            }
+            KeyCode::Char(MOVE_DOWN) | KeyCode::Down => self.help.scroll_down(1),
+            KeyCode::Char(MOVE_UP) | KeyCode::Up => self.help.scroll_up(1),
+            KeyCode::PageDown => self.help.scroll_down(self.help.page()),
+            KeyCode::PageUp => self.help.scroll_up(self.help.page()),
+            _ => {}
+        }
+    }
+
+    /// Keys shown in the footer, the ones that make sense in the current mode and selection
+    fn hints(&self) -> Vec<KeyHelp> {
+        let fixed = |hints: &[(&str, &'static str)]| -> Vec<KeyHelp> {
+            hints
+                .iter()
+                .map(|(keys, text)| (keys.to_string(), *text))
+                .collect()
+        };
+        let up_down = format!("{MOVE_DOWN}/{MOVE_UP}");
+        match self.input_mode {
+            InputMode::Normal => {
+                let mut hints = Vec::new();
+                let mut add = |actions: &[Action], text| {
+                    let keys: Option<Vec<String>> = actions
+                        .iter()
+                        .map(|action| self.keymap.label(*action))
+                        .collect();
+                    if let Some(keys) = keys {
+                        hints.push((keys.join("/"), text));
+                    }
+                };
+                add(&[Action::MoveDown, Action::MoveUp], "move");
+                add(&[Action::FocusLeft, Action::FocusRight], "lists");
+                if self.selected_task().is_some() {
+                    add(&[Action::EditTask], "edit");
+                    add(&[Action::OpenDetail], "details");
+                    add(&[Action::MoveToLeft, Action::MoveToRight], "move card");
+                    add(&[Action::DeleteTask], "delete");
+                    add(&[Action::ArchiveTask], "archive");
+                }
+                add(&[Action::NewTask], "new card");
+                if self.search.is_some() {
+                    add(&[Action::NextMatch, Action::PreviousMatch], "matches");
+                    add(&[Action::Back], "clear search");
+                } else {
+                    add(&[Action::Search], "search");
+                }
+                add(&[Action::Help], "all keys");
+                add(&[Action::Exit], "quit");
+                hints
+            }
+            InputMode::Editing if self.input_target == InputTarget::Search => fixed(&[
+                ("Enter", "first match"),
+                ("Esc", "clear"),
+                ("/regex/", "regex search"),
+            ]),
+            InputMode::Editing => fixed(&[
+                ("Enter", "save"),
+                ("Alt-Enter", "new line"),
+                ("Ctrl-w", "delete word"),
+                ("Esc", "cancel"),
+            ]),
+            InputMode::Detail => fixed(&[
+                (&up_down, "choose field"),
+                (&format!("{EDIT_TASK}/Enter"), "edit"),
+                ("Esc", "close"),
+            ]),
+            InputMode::BoardPicker => fixed(&[
+                ("Enter", "open"),
+                (&NEW_BOARD.to_string(), "new"),
+                (&RENAME_BOARD.to_string(), "rename"),
+                (&DELETE_BOARD.to_string(), "delete"),
+                ("Esc", "close"),
+            ]),
+            InputMode::TagPicker => fixed(&[
+                ("Space", "show/hide"),
+                (&TAG_COLOR.to_string(), "next color"),
+                ("Esc", "close"),
+            ]),
+            InputMode::Archive => fixed(&[
+                (&up_down, "choose"),
+                ("Enter", "restore"),
+                ("1-9", "restore to column"),
+                (&SEARCH.to_string(), "search"),
+                ("Esc", "close"),
+            ]),
+            InputMode::Confirm => fixed(&[
+                (&format!("{CONFIRM_YES}/Enter"), "yes"),
+                (&format!("{CONFIRM_NO}/Esc"), "no"),
+            ]),
+            InputMode::Help => fixed(&[
+                (&up_down, "scroll"),
+                ("PageDown/PageUp", "page"),
+                ("Esc", "close"),
+            ]),
+        }
+    }
+
+    fn board_picker_input(&mut self, key: KeyEvent) {
         match key.code {
-            KeyCode::Char(EXIT) | KeyCode::Esc => self.handle_exit(), // TODO: Persistir los
-            // cambios
-            KeyCode::Char(MOVE_DOWN) | KeyCode::Down => self.current_column().select_next(),
-            KeyCode::Char(MOVE_UP) | KeyCode::Up => self.current_column().select_previous(),
-            KeyCode::Char(CHANGE_INPUT_MODE) => self.change_input_mode(),
-            KeyCode::Char(TODO_LIST) => self.change_focus(SelectedColumn::Todo),
-            KeyCode::Char(DOING_LIST) => self.change_focus(SelectedColumn::Doing),
-            KeyCode::Char(DONE_LIST) => self.change_focus(SelectedColumn::Done),
-            KeyCode::Char(MOVE_TO_TODO) => self.move_item(SelectedColumn::Todo),
-            KeyCode::Char(MOVE_TO_DOING) => self.move_item(SelectedColumn::Doing),
-            KeyCode::Char(MOVE_TO_DONE) => self.move_item(SelectedColumn::Done),
-            KeyCode::Char(DELETE_TASK) => self.delete_item(),
+            KeyCode::Char(EXIT) | KeyCode::Char(BOARD_PICKER) | KeyCode::Esc => {
+                self.input_mode = InputMode::Normal
+            }
+            KeyCode::Char(MOVE_DOWN) | KeyCode::Down => self.board_picker.select_next(),
+            KeyCode::Char(MOVE_UP) | KeyCode::Up => self.board_picker.select_previous(),
+            KeyCode::Enter => {
+                if let Some(board) = self.board_picker.selected_board() {
+                    let board = board.to_string();
+                    // Errors keep the current board on screen
+                    let switched = self.switch_board(board);
+                    self.report(switched);
+                }
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Char(NEW_BOARD) => self.open_input(InputTarget::NewBoard),
+            KeyCode::Char(RENAME_BOARD) => self.open_input(InputTarget::RenameBoard),
+            KeyCode::Char(DELETE_BOARD) => {
+                if let Some(board) = self.board_picker.selected_board() {
+                    let question = format!("Delete the board '{board}' and all its cards?");
+                    self.ask(Deletion::Board, question);
+                }
+            }
             _ => {}
         }
     }
 
+    fn tag_picker_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char(EXIT) | KeyCode::Char(TAG_FILTER) | KeyCode::Esc => {
+                self.input_mode = InputMode::Normal
+            }
+            KeyCode::Char(MOVE_DOWN) | KeyCode::Down => self.tag_picker.select_next(),
+            KeyCode::Char(MOVE_UP) | KeyCode::Up => self.tag_picker.select_previous(),
+            KeyCode::Char(' ') | KeyCode::Enter => {
+                if let Some(tag) = self.tag_picker.selected_tag() {
+                    self.filter.toggle_tag(tag);
+                }
+            }
+            KeyCode::Char(TAG_COLOR) => {
+                if let Some(tag) = self.tag_picker.selected_tag() {
+                    self.palette.cycle(tag);
+                    self.mark_dirty();
+                }
+            }
+            _ => {}
+        }
+        self.tag_picker.load(&self.palette, &self.filter);
+    }
+
+    fn open_tag_picker(&mut self) {
+        self.tag_picker.load(&self.palette, &self.filter);
+        self.input_mode = InputMode::TagPicker;
+    }
+
+    fn detail_mode_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char(EXIT) | KeyCode::Esc => self.input_mode = InputMode::Normal,
+            KeyCode::Char(MOVE_DOWN) | KeyCode::Down => {
+                self.detail_field = self.detail_field.next()
+            }
+            KeyCode::Char(MOVE_UP) | KeyCode::Up => {
+                self.detail_field = self.detail_field.previous()
+            }
+            KeyCode::Char(EDIT_TASK) | KeyCode::Enter => {
+                self.open_input(InputTarget::EditTask(self.detail_field))
+            }
+            _ => {}
+        }
+    }
+
+    /// Shows the detail of the selected card
+    fn open_detail(&mut self) {
+        if self.selected_task().is_some() {
+            self.detail_field = DetailField::default();
+            self.input_mode = InputMode::Detail;
+        }
+    }
+
     fn handle_exit(&mut self) {
-        Persistence::persist(&self.todo_list, &self.doing_list, &self.done_list);
-        self.should_exit = true;
+        match self.save() {
+            Ok(()) => self.should_exit = true,
+            Err(_) if self.force_exit => self.should_exit = true,
+            Err(error) => {
+                self.fail(format!(
+                    "Could not save: {error}. Press {EXIT} again to quit without saving"
+                ));
+                self.force_exit = true;
+            }
+        }
+    }
+
+    /// Shows a short message, like "Saved", in the footer
+    fn notify(&mut self, message: String) {
+        self.status = Some((Status::Info(message), Instant::now()));
+    }
+
+    /// Shows an error in the footer
+    fn fail(&mut self, message: String) {
+        self.status = Some((Status::Error(message), Instant::now()));
+    }
+
+    /// Takes toasts out of the footer once they have been shown for `TOAST_DURATION`
+    fn expire_status(&mut self) {
+        if let Some((Status::Info(_), since)) = &self.status
+            && since.elapsed() >= TOAST_DURATION
+        {
+            self.status = None;
+        }
+    }
+
+    /// Keeps the error of `result` to show it in the footer
+    fn report<T>(&mut self, result: Result<T>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(error) => {
+                self.fail(error.to_string());
+                None
+            }
+        }
     }
 
     fn editing_mode_input(&mut self, key: KeyEvent) {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
         match key.code {
-            KeyCode::Enter => self.push_message(),
+            // Plain Enter submits, with Alt or Shift it starts a new line
+            KeyCode::Enter
+                if key
+                    .modifiers
+                    .intersects(KeyModifiers::ALT | KeyModifiers::SHIFT) =>
+            {
+                self.input_box.enter_char('\n')
+            }
+            KeyCode::Enter => self.submit_input(),
+            KeyCode::Char(DELETE_WORD) if control => self.input_box.delete_word(),
+            KeyCode::Char(_) if control => {}
             KeyCode::Char(to_insert) => self.input_box.enter_char(to_insert),
             KeyCode::Backspace => self.input_box.delete_char(),
+            KeyCode::Delete => self.input_box.delete_char_forward(),
+            KeyCode::Left if control => self.input_box.move_word_left(),
+            KeyCode::Right if control => self.input_box.move_word_right(),
             KeyCode::Left => self.input_box.move_cursor_left(),
             KeyCode::Right => self.input_box.move_cursor_right(),
-            KeyCode::Esc => self.input_mode = InputMode::Normal,
+            KeyCode::Up => self.input_box.move_cursor_up(),
+            KeyCode::Down => self.input_box.move_cursor_down(),
+            KeyCode::Home => self.input_box.move_cursor_home(),
+            KeyCode::End => self.input_box.move_cursor_end(),
+            KeyCode::Esc => {
+                self.input_box.clear();
+                self.close_input();
+                match self.input_target {
+                    InputTarget::Search => self.search = None,
+                    InputTarget::ArchiveSearch => self.search_archive(None),
+                    _ => {}
+                }
+            }
             _ => {}
         }
+        // Matches follow the query while it is typed
+        if self.input_mode == InputMode::Editing {
+            match self.input_target {
+                InputTarget::Search => self.update_search(),
+                InputTarget::ArchiveSearch => {
+                    if let Some(search) = self.report(Search::parse(self.input_box.value())) {
+                        self.search_archive(search);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Searches for the text of the input box, counting the matches in the popup title
+    fn update_search(&mut self) {
+        let Some(search) = self.report(Search::parse(self.input_box.value())) else {
+            return;
+        };
+        let title = match &search {
+            Some(search) => {
+                let count: usize = self.columns.iter().map(|c| c.search(search).len()).sum();
+                format!("Search, {count} matches")
+            }
+            None => "Search, /regex/ for patterns".to_string(),
+        };
+        self.input_box.set_title(&title);
+        self.search = search;
+    }
+
+    /// Keeps the search and goes to the first match from the selected card on
+    fn submit_search(&mut self) {
+        self.input_box.clear();
+        self.close_input();
+        let on_match = self.search.as_ref().is_some_and(|search| {
+            self.selected_task()
+                .is_some_and(|(_, task)| search.matches(task))
+        });
+        if !on_match {
+            self.jump_to_match(true);
+        }
+    }
+
+    /// Selects the next (or previous) card matching the search, moving across columns and
+    /// wrapping around the board
+    fn jump_to_match(&mut self, forward: bool) {
+        let Some(search) = &self.search else {
+            self.fail(format!(
+                "Nothing to look for, press {} to search",
+                self.key_label(Action::Search)
+            ));
+            return;
+        };
+        // Matches as (column, row, item), in reading order
+        let matches: Vec<(usize, usize, usize)> = self
+            .columns
+            .iter()
+            .enumerate()
+            .flat_map(|(c, column)| {
+                column
+                    .search(search)
+                    .into_iter()
+                    .map(move |(row, item)| (c, row, item))
+            })
+            .collect();
+        if matches.is_empty() {
+            self.fail(format!("No card matches '{}'", search.query()));
+            return;
+        }
+
+        let row = self.columns[self.selected_column].selected_position();
+        let current = (self.selected_column, row);
+        let found = if forward {
+            // No selection sits before the first row of the column
+            matches
+                .iter()
+                .find(|(c, r, _)| (*c, Some(*r)) > current)
+                .or(matches.first())
+        } else {
+            let current = (self.selected_column, row.unwrap_or(usize::MAX));
+            matches
+                .iter()
+                .rev()
+                .find(|(c, r, _)| (*c, *r) < current)
+                .or(matches.last())
+        };
+        if let Some(&(column, _, item)) = found {
+            self.focus((column, Some(item)));
+        }
+    }
+
+    /// Keys of `action` to mention in messages
+    fn key_label(&self, action: Action) -> String {
+        self.keymap
+            .label(action)
+            .unwrap_or_else(|| format!("the key of {action:?}"))
     }
 
     // Helper to get the currently active list
     fn current_column(&mut self) -> &mut KanbanColumn {
-        match self.selected_column {
-            SelectedColumn::Todo => &mut self.todo_list,
-            SelectedColumn::Doing => &mut self.doing_list,
-            SelectedColumn::Done => &mut self.done_list,
+        &mut self.columns[self.selected_column]
+    }
+
+    /// Focuses the given column and selects the given card in it
+    fn focus(&mut self, (column, card): Focus) {
+        self.selected_column = column;
+        self.current_column().select(card);
+    }
+
+    /// Runs a change of the board and records it so it can be undone
+    fn execute(&mut self, command: Command) -> Focus {
+        let focus = self
+            .history
+            .execute(command, &mut self.columns, &mut self.archive);
+        self.mark_dirty();
+        focus
+    }
+
+    fn undo(&mut self) {
+        if let Some(focus) = self.history.undo(&mut self.columns, &mut self.archive) {
+            self.focus(focus);
+            self.mark_dirty();
         }
     }
 
-    /// Gives the focus to a new column
-    fn change_focus(&mut self, new_focus: SelectedColumn) {
-        self.current_column().clear_select();
+    fn redo(&mut self) {
+        if let Some(focus) = self.history.redo(&mut self.columns, &mut self.archive) {
+            self.focus(focus);
+            self.mark_dirty();
+        }
+    }
+
+    /// Gives the focus to a new column, back on the card it had selected last. Indexes outside
+    /// the board are ignored
+    fn change_focus(&mut self, new_focus: usize) {
+        if new_focus >= self.columns.len() {
+            return;
+        }
         self.selected_column = new_focus;
-        self.current_column().select_next();
+        if self.current_column().selected().is_none() {
+            self.current_column().select_next();
+        }
+    }
+
+    /// Change the selected taks from the focus column to another one. It lands on the card
+    /// selected in the destination, or on the same row when nothing is selected there
+    fn move_item(&mut self, destination_list: usize) {
+        if let Some((i, _)) = self.selected_task()
+            && let Some(destination) = self.columns.get(destination_list)
+        {
+            let to_position = destination.selected().unwrap_or(i).min(destination.len());
+            self.move_item_to(destination_list, to_position);
+        }
     }
 
-    /// Change the selected taks from the focus column to another one
-    fn move_item(&mut self, destination_list: SelectedColumn) {
-        if self.selected_column == destination_list {
+    /// Moves the selected card into another column, before the card at `to_position`.
+    /// Returns where the card ended up, if it moved
+    fn move_item_to(&mut self, destination_list: usize, to_position: usize) -> Option<Focus> {
+        if self.selected_column == destination_list || destination_list >= self.columns.len() {
+            return None;
+        }
+
+        let (i, task) = self.selected_task()?;
+        let (task, title) = (task.id, short_title(task).to_string());
+        if !self.check_limit(destination_list) {
+            return None;
+        }
+        let focus = self.execute(Command::MoveTask {
+            task,
+            from_column: self.selected_column,
+            from_position: i,
+            to_column: destination_list,
+            to_position,
+        });
+        // A warning about the WIP limit matters more than the toast
+        if self.status.is_none() {
+            let destination = self.columns[destination_list].title();
+            self.notify(format!("Moved '{title}' to {destination}"));
+        }
+        Some(focus)
+    }
+
+    /// Moves the selected card to the display row `row(current row)`, next to the card shown
+    /// there. Cards hidden by the filter keep their place
+    fn reorder_to_row(&mut self, row: fn(usize) -> usize) {
+        let column = &self.columns[self.selected_column];
+        let target = column
+            .selected_position()
+            .and_then(|current| column.shown_at(row(current)));
+        if let Some(target) = target {
+            self.reorder_item(target);
+        }
+    }
+
+    /// Moves the selected card to `position` inside its column, clamped to the last card.
+    /// The selection follows the card
+    fn reorder_item(&mut self, position: usize) {
+        let Some((i, task)) = self.selected_task() else {
+            return;
+        };
+        let task = task.id;
+        let position = position.min(self.columns[self.selected_column].len() - 1);
+        if position == i {
+            return;
+        }
+        let sort = self.columns[self.selected_column].sort();
+        if sort != SortMode::Manual {
+            self.fail(format!(
+                "This column is sorted by {}, press {} until it is manual to reorder it",
+                sort.label(),
+                self.key_label(Action::SortColumn)
+            ));
             return;
         }
+        let focus = self.execute(Command::MoveTask {
+            task,
+            from_column: self.selected_column,
+            from_position: i,
+            to_column: self.selected_column,
+            to_position: position,
+        });
+        self.focus(focus);
+    }
 
-        if let Some(i) = self.current_column().selected() {
-            let item = self.current_column().remove(i);
-            match destination_list {
-                SelectedColumn::Todo => self.todo_list.push(item),
-                SelectedColumn::Doing => self.doing_list.push(item),
-                SelectedColumn::Done => self.done_list.push(item),
+    /// Replaces the priority of the selected card with `change(priority)`
+    fn change_priority(&mut self, change: fn(Option<Priority>) -> Option<Priority>) {
+        if let Some((i, before)) = self.selected_task() {
+            let mut after = before.clone();
+            after.priority = change(before.priority);
+            if after.priority != before.priority {
+                after.touch();
+                self.execute(Command::EditTask {
+                    column: self.selected_column,
+                    position: i,
+                    before: before.clone(),
+                    after,
+                });
             }
         }
     }
 
+    /// Whether a new card may go into `column`. Over the limit the card is refused or goes in
+    /// with a warning, depending on the WIP policy
+    fn check_limit(&mut self, column: usize) -> bool {
+        let column = &self.columns[column];
+        let Some(limit) = column.limit().filter(|_| column.is_full()) else {
+            return true;
+        };
+        match self.wip_policy {
+            WipPolicy::Refuse => {
+                self.fail(format!(
+                    "{} is full ({limit} cards), finish something first",
+                    column.title()
+                ));
+                false
+            }
+            WipPolicy::Warn => {
+                self.fail(format!("{} is over its limit of {limit}", column.title()));
+                true
+            }
+        }
+    }
+
+    /// Sets the WIP limit of the focused column from the input. Empty or 0 removes it
+    fn set_limit(&mut self) {
+        let text = self.input_box.submit_message().unwrap_or_default();
+        let limit = match text.trim() {
+            "" | "0" => None,
+            text => match text.parse::<usize>() {
+                Ok(limit) => Some(limit),
+                Err(_) => {
+                    self.fail(format!("'{text}' is not a number of cards"));
+                    return self.close_input();
+                }
+            },
+        };
+        let before = self.columns[self.selected_column].limit();
+        if limit != before {
+            self.execute(Command::LimitColumn {
+                position: self.selected_column,
+                before,
+                after: limit,
+            });
+        }
+        self.close_input();
+    }
+
+    /// Moves the focused column to the next sort mode
+    fn sort_column(&mut self) {
+        let before = self.columns[self.selected_column].sort();
+        self.execute(Command::SortColumn {
+            position: self.selected_column,
+            before,
+            after: before.next(),
+        });
+    }
+
     fn delete_item(&mut self) {
-        if let Some(i) = self.current_column().selected() {
-            self.current_column().remove(i);
+        if let Some((i, task)) = self.selected_task() {
+            let task = task.clone();
+            let message = format!("Deleted '{}'", short_title(&task));
+            self.execute(Command::DeleteTask {
+                column: self.selected_column,
+                position: i,
+                task,
+            });
+            self.notify(message);
         }
     }
 
-    fn change_input_mode(&mut self) {
-        match self.input_mode {
-            InputMode::Normal => self.input_mode = InputMode::Editing,
-            InputMode::Editing => self.input_mode = InputMode::Normal,
+    /// Takes the selected card off the board, into the archive
+    fn archive_item(&mut self) {
+        if let Some((i, task)) = self.selected_task() {
+            let message = format!("Archived '{}'", short_title(task));
+            let card = ArchivedTask::new(task.clone(), self.columns[self.selected_column].title());
+            self.execute(Command::ArchiveTask {
+                column: self.selected_column,
+                position: i,
+                archived: 0,
+                card,
+            });
+            self.notify(message);
+        }
+    }
+
+    /// Archives the cards of the last column that have been done for longer than the
+    /// auto-archive rule allows. It goes through the history like any other change, so the
+    /// positions kept by older entries still match the board and it can be undone
+    fn archive_done_cards(&mut self) {
+        let AutoArchive::After(days) = self.auto_archive else {
+            return;
+        };
+        let done = self.columns.len() - 1;
+        let now = Utc::now();
+        let mut archived = 0;
+        // Back to front, so the positions still to visit don't shift
+        for i in (0..self.columns[done].len()).rev() {
+            let Some(task) = self.columns[done].get(i) else {
+                continue;
+            };
+            if self.auto_archive.is_due(task, now) {
+                let card = ArchivedTask::new(task.clone(), self.columns[done].title());
+                self.execute(Command::ArchiveTask {
+                    column: done,
+                    position: i,
+                    archived: 0,
+                    card,
+                });
+                archived += 1;
+            }
+        }
+        if archived > 0 {
+            self.notify(format!(
+                "Archived {archived} cards done for more than {days} days"
+            ));
+        }
+    }
+
+    fn open_archive(&mut self) {
+        self.archive_view.load(&self.archive);
+        self.input_mode = InputMode::Archive;
+    }
+
+    fn archive_mode_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc if self.archive_view.search().is_some() => self.search_archive(None),
+            KeyCode::Char(EXIT) | KeyCode::Esc => self.input_mode = InputMode::Normal,
+            KeyCode::Char(MOVE_DOWN) | KeyCode::Down => self.archive_view.select_next(),
+            KeyCode::Char(MOVE_UP) | KeyCode::Up => self.archive_view.select_previous(),
+            KeyCode::Char(SEARCH) => self.open_input(InputTarget::ArchiveSearch),
+            KeyCode::Enter => {
+                if let Some(card) = self
+                    .archive_view
+                    .selected()
+                    .and_then(|i| self.archive.get(i))
+                {
+                    // Back where it came from, or on the focused column when that one is gone
+                    let column = self
+                        .columns
+                        .iter()
+                        .position(|column| column.title() == card.column)
+                        .unwrap_or(self.selected_column);
+                    self.restore_item(column);
+                }
+            }
+            KeyCode::Char(c @ '1'..='9') => self.restore_item(c as usize - '1' as usize),
+            _ => {}
+        }
+    }
+
+    /// Lists only the archived cards matching `search`, or all of them
+    fn search_archive(&mut self, search: Option<Search>) {
+        self.archive_view.set_search(search);
+        self.archive_view.load(&self.archive);
+    }
+
+    /// Puts the card selected in the archive back at the bottom of `column`
+    fn restore_item(&mut self, column: usize) {
+        let Some(archived) = self.archive_view.selected() else {
+            return;
+        };
+        if column >= self.columns.len() || !self.check_limit(column) {
+            return;
+        }
+        let card = self.archive.get(archived).cloned();
+        if let Some(card) = card {
+            let message = format!(
+                "Restored '{}' to {}",
+                short_title(&card.task),
+                self.columns[column].title()
+            );
+            let focus = self.execute(Command::RestoreTask {
+                column,
+                position: self.columns[column].len(),
+                archived,
+                card,
+            });
+            self.focus(focus);
+            self.archive_view.load(&self.archive);
+            // A warning about the WIP limit matters more than the toast
+            if self.status.is_none() {
+                self.notify(message);
+            }
+        }
+    }
+
+    /// Position and card selected in the focused column, if the selection points to a card
+    fn selected_task(&self) -> Option<(usize, &Task)> {
+        let column = &self.columns[self.selected_column];
+        let i = column.selected()?;
+        column.get(i).map(|task| (i, task))
+    }
+
+    /// Removes the focused column together with its tasks. The board always keeps one column
+    fn delete_column(&mut self) {
+        if self.columns.len() == 1 {
+            return;
+        }
+        let column = ColumnData::from(&self.columns[self.selected_column]);
+        let message = format!("Deleted the column '{}'", column.title);
+        let focus = self.execute(Command::DeleteColumn {
+            position: self.selected_column,
+            column,
+        });
+        self.focus(focus);
+        self.notify(message);
+    }
+
+    /// Swaps the focused column with the one at `destination`. Focus follows the moved column
+    fn swap_column(&mut self, destination: usize) {
+        if destination >= self.columns.len() {
+            return;
+        }
+        let focus = self.execute(Command::SwapColumns {
+            first: self.selected_column,
+            second: destination,
+        });
+        self.focus(focus);
+    }
+
+    /// Opens the input popup, pre-filled when editing something that already has a value
+    fn open_input(&mut self, target: InputTarget) {
+        match target {
+            InputTarget::NewTask => self.input_box.set_title("New task"),
+            InputTarget::EditTask(field) => {
+                let Some((_, task)) = self.selected_task() else {
+                    return;
+                };
+                let text = match field {
+                    DetailField::Title => task.input(),
+                    DetailField::Priority => {
+                        task.priority.map(|p| p.to_string()).unwrap_or_default()
+                    }
+                    DetailField::Due => task
+                        .due
+                        .map(|due| due.format("%Y-%m-%d").to_string())
+                        .unwrap_or_default(),
+                    DetailField::Tags => join_tags(&task.tags),
+                    DetailField::Description => task.description.clone(),
+                };
+                self.input_box
+                    .set_title(&format!("Edit {}", field.label().to_lowercase()));
+                self.input_box.set_input(text);
+            }
+            InputTarget::NewColumn => self.input_box.set_title("New column"),
+            InputTarget::RenameColumn => {
+                let title = self.current_column().title().to_string();
+                self.input_box.set_title("Rename column");
+                self.input_box.set_input(title);
+            }
+            InputTarget::ColumnLimit => {
+                let limit = self.current_column().limit();
+                self.input_box.set_title("WIP limit, empty for none");
+                self.input_box
+                    .set_input(limit.map(|limit| limit.to_string()).unwrap_or_default());
+            }
+            InputTarget::Search => {
+                let query = self.search.as_ref().map(|s| s.query().to_string());
+                self.input_box.set_input(query.unwrap_or_default());
+                self.update_search();
+            }
+            InputTarget::ArchiveSearch => {
+                let query = self.archive_view.search().map(|s| s.query().to_string());
+                self.input_box
+                    .set_title("Search the archive, /regex/ for patterns");
+                self.input_box.set_input(query.unwrap_or_default());
+            }
+            InputTarget::NewBoard => self.input_box.set_title("New board"),
+            InputTarget::RenameBoard => {
+                let Some(board) = self.board_picker.selected_board() else {
+                    return;
+                };
+                let board = board.to_string();
+                self.input_box.set_title("Rename board");
+                self.input_box.set_input(board);
+            }
         }
+        self.input_target = target;
+        self.previous_mode = self.input_mode;
+        self.input_mode = InputMode::Editing;
     }
 
-    /// Push new task to the TODO column
+    /// Leaves the input popup, going back to whatever was open before it
+    fn close_input(&mut self) {
+        self.input_mode = self.previous_mode;
+    }
+
+    fn submit_input(&mut self) {
+        match self.input_target {
+            InputTarget::NewTask => self.push_message(),
+            InputTarget::EditTask(field) => self.edit_item(field),
+            InputTarget::NewColumn => self.push_column(),
+            InputTarget::RenameColumn => self.rename_column(),
+            InputTarget::ColumnLimit => self.set_limit(),
+            InputTarget::Search => self.submit_search(),
+            InputTarget::ArchiveSearch => {
+                self.input_box.clear();
+                self.close_input();
+            }
+            InputTarget::NewBoard => self.push_board(),
+            InputTarget::RenameBoard => self.rename_board(),
+        }
+    }
+
+    /// Push new task to the first column
     fn push_message(&mut self) {
+        // Nothing is added from an empty box, and a refused card stays in it
+        if self.input_box.value().is_empty() || !self.check_limit(0) {
+            return;
+        }
         if let Some(message) = self.input_box.submit_message() {
-            self.todo_list.push(message);
+            let task = Task::from_input(&message);
+            self.palette.add(&task.tags);
+            self.execute(Command::AddTask {
+                column: 0,
+                position: self.columns[0].len(),
+                task,
+            });
+        }
+    }
+
+    /// Saves the edited text back to `field` of the selected card, keeping its place in the
+    /// column. Titles can't be left empty, descriptions can
+    fn edit_item(&mut self, field: DetailField) {
+        let text = self.input_box.submit_message();
+        if let Some((i, before)) = self.selected_task() {
+            let before = before.clone();
+            let mut after = before.clone();
+            match (field, text) {
+                (DetailField::Title, Some(title)) => after.set_input(&title),
+                (DetailField::Title, None) => {}
+                (DetailField::Priority, text) => {
+                    let priority = Priority::parse(&text.unwrap_or_default());
+                    match self.report(priority) {
+                        Some(priority) => after.priority = priority,
+                        None => return self.close_input(),
+                    }
+                }
+                (DetailField::Due, text) => {
+                    let due = parse_optional_due(&text.unwrap_or_default(), today());
+                    match self.report(due) {
+                        Some(due) => after.due = due,
+                        None => return self.close_input(),
+                    }
+                }
+                (DetailField::Tags, text) => {
+                    after.tags = parse_tags(&text.unwrap_or_default());
+                }
+                (DetailField::Description, text) => after.description = text.unwrap_or_default(),
+            }
+            if after != before {
+                after.touch();
+                self.palette.add(&after.tags);
+                self.execute(Command::EditTask {
+                    column: self.selected_column,
+                    position: i,
+                    before,
+                    after,
+                });
+            }
+        }
+        self.close_input();
+    }
+
+    /// Adds a new column right after the focused one and leaves the input mode
+    fn push_column(&mut self) {
+        if let Some(title) = self.input_box.submit_message() {
+            let focus = self.execute(Command::AddColumn {
+                position: self.selected_column + 1,
+                column: ColumnData {
+                    title,
+                    tasks: Vec::new(),
+                    sort: SortMode::default(),
+                    limit: None,
+                },
+            });
+            self.focus(focus);
+        }
+        self.close_input();
+    }
+
+    fn rename_column(&mut self) {
+        if let Some(title) = self.input_box.submit_message() {
+            self.execute(Command::RenameColumn {
+                position: self.selected_column,
+                before: self.columns[self.selected_column].title().to_string(),
+                after: title,
+            });
+        }
+        self.close_input();
+    }
+
+    /// Saves the current board and lists every board of the workspace in the picker
+    fn open_board_picker(&mut self) {
+        let saved = self.save();
+        self.report(saved);
+        // A workspace that can't be read can't be browsed either
+        let boards = self.storage.board_names();
+        if let Some(boards) = self.report(boards) {
+            self.board_picker.load(boards, &self.board_name);
+            self.input_mode = InputMode::BoardPicker;
+        }
+    }
+
+    /// Saves the current board and shows `board` instead, creating it when needed
+    fn switch_board(&mut self, board: String) -> Result<()> {
+        if board == self.board_name {
+            return Ok(());
+        }
+        let (columns, history, palette, archive) = load_board(self.storage.as_ref(), &board)?;
+        self.save()?;
+        self.board_name = board;
+        self.columns = columns;
+        self.history = history;
+        self.palette = palette;
+        self.archive = archive;
+        self.filter = Filter::default();
+        self.selected_column = 0;
+        self.current_column().select_next();
+        self.archive_done_cards();
+        Ok(())
+    }
+
+    /// Creates a new board and switches to it. Names already taken are refused
+    fn push_board(&mut self) {
+        if let Some(board) = self.input_box.submit_message() {
+            let boards = self.storage.board_names();
+            if let Some(boards) = self.report(boards) {
+                if boards.contains(&board) {
+                    self.fail(format!("A board called '{board}' already exists"));
+                } else {
+                    let switched = self.switch_board(board);
+                    self.report(switched);
+                }
+            }
+        }
+        self.input_mode = InputMode::Normal;
+    }
+
+    fn rename_board(&mut self) {
+        let old_name = self.board_picker.selected_board().map(str::to_string);
+        if let (Some(old_name), Some(new_name)) = (old_name, self.input_box.submit_message()) {
+            // Renaming onto an existing board is refused by the storage
+            let renamed = self.storage.rename_board(&old_name, &new_name);
+            if self.report(renamed).is_some() && old_name == self.board_name {
+                self.board_name = new_name;
+            }
+        }
+        self.open_board_picker();
+    }
+
+    /// Deletes the board selected in the picker. The workspace always keeps one board, and
+    /// deleting the board on screen shows the first remaining one
+    fn delete_board(&mut self) {
+        let Some(board) = self.board_picker.selected_board().map(str::to_string) else {
+            return;
+        };
+        let boards = self.storage.board_names();
+        let Some(boards) = self.report(boards) else {
+            return;
+        };
+        let Some(fallback) = boards.into_iter().find(|name| *name != board) else {
+            return;
+        };
+
+        if board == self.board_name {
+            let switched = self.switch_board(fallback);
+            if self.report(switched).is_none() {
+                return;
+            }
+        }
+        let deleted = self.storage.delete_board(&board);
+        if self.report(deleted).is_some() {
+            self.open_board_picker();
+            self.notify(format!("Deleted the board '{board}'"));
+        }
+    }
+}
+
+/// First line of the title of `task`, to name it in messages
+fn short_title(task: &Task) -> &str {
+    task.title.lines().next().unwrap_or_default()
+}
+
+/// Raises `terminate` when the process is asked to stop, so the board can be saved first
+#[cfg(unix)]
+fn register_signals(terminate: &Arc<AtomicBool>) -> Result<()> {
+    use signal_hook::consts::{SIGHUP, SIGINT, SIGQUIT, SIGTERM};
+
+    for signal in [SIGHUP, SIGINT, SIGQUIT, SIGTERM] {
+        signal_hook::flag::register(signal, Arc::clone(terminate))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn register_signals(_terminate: &Arc<AtomicBool>) -> Result<()> {
+    Ok(())
+}
+
+/// Columns given to boards that are created from scratch
+fn default_columns() -> Vec<KanbanColumn> {
+    ["TODO", "Doing", "Done"]
+        .into_iter()
+        .map(|title| KanbanColumn::new(title.to_string()))
+        .collect()
+}
+
+/// Loads the columns, history and tag palette of `board`. New or empty boards get the default
+/// columns and are saved right away, so the first run leaves a data file behind
+fn load_board(
+    storage: &dyn Storage,
+    board: &str,
+) -> Result<(Vec<KanbanColumn>, History, Palette, Archive)> {
+    match storage.load_board(board)?.map(BoardData::into_parts) {
+        Some(parts) if !parts.0.is_empty() => Ok(parts),
+        _ => {
+            let columns = default_columns();
+            let history = History::default();
+            let palette = Palette::default();
+            let archive = Archive::default();
+            let data = BoardData::new(board, &columns, &history, &palette, &archive);
+            storage.save_board(&data)?;
+            Ok((columns, history, palette, archive))
         }
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::SqliteStorage;
+    use chrono::TimeDelta;
+
+    fn kanban() -> Kanban {
+        Kanban::new(
+            Box::new(SqliteStorage::in_memory().unwrap()),
+            None,
+            WipPolicy::default(),
+            Keymap::default(),
+            Theme::default(),
+            AutoArchive::Off,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_auto_archive_can_be_undone() {
+        let mut kanban = kanban();
+        let done = kanban.columns.len() - 1;
+        let mut task = Task::new("Task 1".to_string());
+        task.created_at -= TimeDelta::days(10);
+        kanban.execute(Command::AddTask {
+            column: done,
+            position: 0,
+            task,
+        });
+
+        kanban.auto_archive = AutoArchive::After(7);
+        kanban.archive_done_cards();
+        assert!(kanban.columns[done].is_empty());
+        assert_eq!(kanban.archive.len(), 1);
+
+        kanban.undo();
+        assert_eq!(kanban.columns[done].len(), 1);
+        assert!(kanban.archive.is_empty());
+        kanban.undo();
+        assert!(kanban.columns[done].is_empty());
+    }
+
+    #[test]
+    fn test_empty_input_doesnt_check_the_limit() {
+        let mut kanban = kanban();
+        kanban.columns[0].set_limit(Some(0));
+
+        kanban.push_message();
+        assert!(kanban.status.is_none());
+        assert!(kanban.columns[0].is_empty());
+    }
+
+    #[test]
+    fn test_reordering_in_place_is_not_an_error() {
+        let mut kanban = kanban();
+        kanban.execute(Command::AddTask {
+            column: 0,
+            position: 0,
+            task: Task::new("Task 1".to_string()),
+        });
+        kanban.columns[0].set_sort(SortMode::Priority);
+        kanban.columns[0].select(Some(0));
+
+        kanban.reorder_item(0);
+        assert!(kanban.status.is_none());
+    }
+}
+
 // #[cfg(test)]
 // mod test {
 //     use super::*;
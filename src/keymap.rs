@@ -0,0 +1,524 @@
+use color_eyre::{Result, eyre::eyre};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use std::{collections::BTreeMap, env, fmt, path::PathBuf, str::FromStr};
+
+use crate::{
+    config,
+    constants::{
+        ARCHIVE_TASK, BOARD_PICKER, CHANGE_INPUT_MODE, DELETE_COLUMN, DELETE_TASK, DUE_FILTER,
+        EDIT_TASK, EXIT, FOCUS_LEFT, FOCUS_RIGHT, HELP, MOVE_COLUMN_LEFT, MOVE_COLUMN_RIGHT,
+        MOVE_DOWN, MOVE_TASK_BOTTOM, MOVE_TASK_DOWN, MOVE_TASK_TOP, MOVE_TASK_UP, MOVE_TO_LEFT,
+        MOVE_TO_RIGHT, MOVE_UP, NEW_COLUMN, NEXT_MATCH, OPEN_ARCHIVE, PREVIOUS_MATCH,
+        PRIORITY_DOWN, PRIORITY_UP, REDO, RENAME_COLUMN, SAVE, SEARCH, SELECT_FIRST, SELECT_LAST,
+        SET_LIMIT, SORT_COLUMN, TAG_FILTER, UNDO,
+    },
+};
+
+/// Environment variable pointing to the key bindings file when no flag is given
+pub const KEYMAP_ENV: &str = "KANBAN_KEYMAP";
+/// Name of the key bindings file inside the config directory
+pub const KEYMAP_FILE: &str = "keys";
+
+/// Everything that can be bound to a key on the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Exit,
+    /// Clears the search, or exits when there is none
+    Back,
+    Save,
+    Undo,
+    Redo,
+    MoveDown,
+    MoveUp,
+    SelectFirst,
+    SelectLast,
+    FocusLeft,
+    FocusRight,
+    NewTask,
+    EditTask,
+    OpenDetail,
+    DeleteTask,
+    ArchiveTask,
+    MoveToLeft,
+    MoveToRight,
+    MoveTaskUp,
+    MoveTaskDown,
+    MoveTaskTop,
+    MoveTaskBottom,
+    PriorityUp,
+    PriorityDown,
+    SortColumn,
+    SetLimit,
+    NewColumn,
+    RenameColumn,
+    DeleteColumn,
+    MoveColumnLeft,
+    MoveColumnRight,
+    BoardPicker,
+    OpenArchive,
+    TagFilter,
+    DueFilter,
+    Search,
+    NextMatch,
+    PreviousMatch,
+    Help,
+}
+
+impl Action {
+    /// Every action, in the order the help lists them
+    pub const ALL: [Action; 39] = [
+        Action::MoveDown,
+        Action::MoveUp,
+        Action::SelectFirst,
+        Action::SelectLast,
+        Action::FocusLeft,
+        Action::FocusRight,
+        Action::NewTask,
+        Action::EditTask,
+        Action::OpenDetail,
+        Action::DeleteTask,
+        Action::ArchiveTask,
+        Action::MoveToLeft,
+        Action::MoveToRight,
+        Action::MoveTaskUp,
+        Action::MoveTaskDown,
+        Action::MoveTaskTop,
+        Action::MoveTaskBottom,
+        Action::PriorityUp,
+        Action::PriorityDown,
+        Action::NewColumn,
+        Action::RenameColumn,
+        Action::DeleteColumn,
+        Action::MoveColumnLeft,
+        Action::MoveColumnRight,
+        Action::SortColumn,
+        Action::SetLimit,
+        Action::Search,
+        Action::NextMatch,
+        Action::PreviousMatch,
+        Action::TagFilter,
+        Action::DueFilter,
+        Action::BoardPicker,
+        Action::OpenArchive,
+        Action::Undo,
+        Action::Redo,
+        Action::Save,
+        Action::Help,
+        Action::Back,
+        Action::Exit,
+    ];
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Exit => "Quit",
+            Action::Back => "Clear the search, or quit",
+            Action::Save => "Save now",
+            Action::Undo => "Undo",
+            Action::Redo => "Redo",
+            Action::MoveDown => "Select the card below",
+            Action::MoveUp => "Select the card above",
+            Action::SelectFirst => "Select the first card",
+            Action::SelectLast => "Select the last card",
+            Action::FocusLeft => "Focus the column on the left",
+            Action::FocusRight => "Focus the column on the right",
+            Action::NewTask => "New card in the first column",
+            Action::EditTask => "Edit the card",
+            Action::OpenDetail => "Show the card details",
+            Action::DeleteTask => "Delete the card",
+            Action::ArchiveTask => "Archive the card",
+            Action::MoveToLeft => "Move the card to the column on the left",
+            Action::MoveToRight => "Move the card to the column on the right",
+            Action::MoveTaskUp => "Move the card up",
+            Action::MoveTaskDown => "Move the card down",
+            Action::MoveTaskTop => "Move the card to the top",
+            Action::MoveTaskBottom => "Move the card to the bottom",
+            Action::PriorityUp => "Raise the priority",
+            Action::PriorityDown => "Lower the priority",
+            Action::SortColumn => "Change how the column is sorted",
+            Action::SetLimit => "Set the WIP limit of the column",
+            Action::NewColumn => "New column",
+            Action::RenameColumn => "Rename the column",
+            Action::DeleteColumn => "Delete the column and its cards",
+            Action::MoveColumnLeft => "Move the column left",
+            Action::MoveColumnRight => "Move the column right",
+            Action::BoardPicker => "Switch boards",
+            Action::OpenArchive => "Browse and restore archived cards",
+            Action::TagFilter => "Filter by tag",
+            Action::DueFilter => "Show only cards due this week",
+            Action::Search => "Search",
+            Action::NextMatch => "Next match",
+            Action::PreviousMatch => "Previous match",
+            Action::Help => "Show every key",
+        }
+    }
+}
+
+/// One key press together with the modifiers held
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl Chord {
+    /// Shift is dropped from characters since it is already part of them, `K` not `Shift-k`
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let modifiers = match code {
+            KeyCode::Char(_) => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        };
+        Chord { code, modifiers }
+    }
+
+    pub fn char(c: char) -> Self {
+        Chord::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    pub fn ctrl(c: char) -> Self {
+        Chord::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+}
+
+impl From<KeyEvent> for Chord {
+    fn from(key: KeyEvent) -> Self {
+        Chord::new(key.code, key.modifiers)
+    }
+}
+
+/// Keys written by name in the bindings file
+const NAMED_KEYS: [(&str, KeyCode); 13] = [
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("space", KeyCode::Char(' ')),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+];
+
+const MODIFIERS: [(&str, KeyModifiers); 3] = [
+    ("ctrl-", KeyModifiers::CONTROL),
+    ("alt-", KeyModifiers::ALT),
+    ("shift-", KeyModifiers::SHIFT),
+];
+
+/// Parses `K`, `ctrl-r`, `alt-enter` or `space`
+impl FromStr for Chord {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut key = s;
+        while let Some((prefix, modifier)) = MODIFIERS.iter().find(|(prefix, _)| {
+            key.len() > prefix.len()
+                && key
+                    .get(..prefix.len())
+                    .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+        }) {
+            modifiers |= *modifier;
+            key = &key[prefix.len()..];
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => NAMED_KEYS
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(key))
+                .map(|(_, code)| *code)
+                .ok_or_else(|| eyre!("Unknown key '{s}'"))?,
+        };
+        Ok(Chord::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (prefix, modifier) in MODIFIERS {
+            if self.modifiers.contains(modifier) {
+                let name = &prefix[..prefix.len() - 1];
+                write!(f, "{}{}-", name[..1].to_uppercase(), &name[1..])?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::Esc => write!(f, "Esc"),
+            code => write!(f, "{code}"),
+        }
+    }
+}
+
+/// Parses a key sequence. Chords are separated by spaces, and a word that isn't a key name is
+/// read one character at a time, so `gg` and `g g` are the same sequence
+pub fn parse_keys(text: &str) -> Result<Vec<Chord>> {
+    let mut keys = Vec::new();
+    for word in text.split_whitespace() {
+        match word.parse::<Chord>() {
+            Ok(chord) => keys.push(chord),
+            Err(error) if word.contains('-') => return Err(error),
+            Err(_) => keys.extend(word.chars().map(Chord::char)),
+        }
+    }
+    if keys.is_empty() {
+        return Err(eyre!("Empty key sequence"));
+    }
+    Ok(keys)
+}
+
+/// Key sequence written back for the user, `gg`, `Ctrl-r` or `g Enter`
+pub fn format_keys(keys: &[Chord]) -> String {
+    let plain = keys.iter().all(|chord| {
+        chord.modifiers.is_empty() && matches!(chord.code, KeyCode::Char(c) if c != ' ')
+    });
+    let separator = if plain { "" } else { " " };
+    keys.iter()
+        .map(Chord::to_string)
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// Result of looking up the keys pressed so far
+#[derive(Debug, PartialEq)]
+pub enum Lookup {
+    Action(Action),
+    /// The keys start a longer sequence, wait for the next one
+    Pending,
+    Unbound,
+}
+
+/// Keys given to an action in the bindings file, one sequence or a list of them
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Keys {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Key sequences bound to each action
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keymap {
+    bindings: Vec<(Vec<Chord>, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        use Action::*;
+        let chars = [
+            (EXIT, Exit),
+            (SAVE, Save),
+            (UNDO, Undo),
+            (MOVE_DOWN, MoveDown),
+            (MOVE_UP, MoveUp),
+            (SELECT_LAST, SelectLast),
+            (FOCUS_LEFT, FocusLeft),
+            (FOCUS_RIGHT, FocusRight),
+            (CHANGE_INPUT_MODE, NewTask),
+            (EDIT_TASK, EditTask),
+            (DELETE_TASK, DeleteTask),
+            (ARCHIVE_TASK, ArchiveTask),
+            (MOVE_TO_LEFT, MoveToLeft),
+            (MOVE_TO_RIGHT, MoveToRight),
+            (MOVE_TASK_UP, MoveTaskUp),
+            (MOVE_TASK_DOWN, MoveTaskDown),
+            (MOVE_TASK_TOP, MoveTaskTop),
+            (MOVE_TASK_BOTTOM, MoveTaskBottom),
+            (PRIORITY_UP, PriorityUp),
+            (PRIORITY_DOWN, PriorityDown),
+            (SORT_COLUMN, SortColumn),
+            (SET_LIMIT, SetLimit),
+            (NEW_COLUMN, NewColumn),
+            (RENAME_COLUMN, RenameColumn),
+            (DELETE_COLUMN, DeleteColumn),
+            (MOVE_COLUMN_LEFT, MoveColumnLeft),
+            (MOVE_COLUMN_RIGHT, MoveColumnRight),
+            (BOARD_PICKER, BoardPicker),
+            (OPEN_ARCHIVE, OpenArchive),
+            (TAG_FILTER, TagFilter),
+            (DUE_FILTER, DueFilter),
+            (SEARCH, Search),
+            (NEXT_MATCH, NextMatch),
+            (PREVIOUS_MATCH, PreviousMatch),
+            (HELP, Help),
+        ];
+        let mut bindings: Vec<_> = chars
+            .into_iter()
+            .map(|(c, action)| (vec![Chord::char(c)], action))
+            .collect();
+        let keys = [
+            (KeyCode::Esc, Back),
+            (KeyCode::Enter, OpenDetail),
+            (KeyCode::Down, MoveDown),
+            (KeyCode::Up, MoveUp),
+            (KeyCode::Left, FocusLeft),
+            (KeyCode::Right, FocusRight),
+        ];
+        bindings.extend(
+            keys.into_iter()
+                .map(|(code, action)| (vec![Chord::new(code, KeyModifiers::NONE)], action)),
+        );
+        bindings.push((vec![Chord::ctrl(REDO)], Redo));
+        bindings.push((vec![Chord::char(SELECT_FIRST); 2], SelectFirst));
+        Keymap { bindings }
+    }
+}
+
+impl Keymap {
+    /// Bindings from the `--keymap` flag, the `KANBAN_KEYMAP` variable or `keys.toml` (or
+    /// `keys.json`) in the config directory, on top of the default ones
+    pub fn resolve(flag: Option<PathBuf>) -> Result<Keymap> {
+        let path = flag
+            .or_else(|| env::var_os(KEYMAP_ENV).map(PathBuf::from))
+            .filter(|path| !path.as_os_str().is_empty())
+            .or_else(|| config::find(KEYMAP_FILE));
+        match path {
+            Some(path) => Keymap::default().with_overrides(config::read(&path)?),
+            None => Ok(Keymap::default()),
+        }
+    }
+
+    /// Replaces the keys of every action in `overrides`. An empty list unbinds the action
+    fn with_overrides(mut self, overrides: BTreeMap<Action, Keys>) -> Result<Keymap> {
+        for (action, keys) in overrides {
+            let keys = match keys {
+                Keys::One(keys) => vec![keys],
+                Keys::Many(keys) => keys,
+            };
+            self.bindings.retain(|(_, bound)| *bound != action);
+            for keys in keys {
+                self.bindings.push((parse_keys(&keys)?, action));
+            }
+        }
+        self.validate()?;
+        Ok(self)
+    }
+
+    /// Fails when a sequence runs two actions, or when it starts a longer one and so would
+    /// never let the longer one be typed
+    fn validate(&self) -> Result<()> {
+        for (i, (keys, action)) in self.bindings.iter().enumerate() {
+            for (other_keys, other) in &self.bindings[i + 1..] {
+                if other_keys.starts_with(keys) || keys.starts_with(other_keys) {
+                    return Err(eyre!(
+                        "Conflicting key bindings: '{}' for {action:?} and '{}' for {other:?}",
+                        format_keys(keys),
+                        format_keys(other_keys)
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn lookup(&self, keys: &[Chord]) -> Lookup {
+        let mut lookup = Lookup::Unbound;
+        for (bound, action) in &self.bindings {
+            if bound == keys {
+                return Lookup::Action(*action);
+            }
+            if bound.starts_with(keys) {
+                lookup = Lookup::Pending;
+            }
+        }
+        lookup
+    }
+
+    /// Every key sequence bound to `action`, in the order they were bound
+    pub fn keys(&self, action: Action) -> impl Iterator<Item = &[Chord]> {
+        self.bindings
+            .iter()
+            .filter(move |(_, bound)| *bound == action)
+            .map(|(keys, _)| keys.as_slice())
+    }
+
+    /// First sequence bound to `action`, ready to show. `None` when it is unbound
+    pub fn label(&self, action: Action) -> Option<String> {
+        self.keys(action).next().map(format_keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_keys() {
+        assert_eq!(parse_keys("gg").unwrap(), [Chord::char('g'); 2]);
+        assert_eq!(parse_keys("g g").unwrap(), [Chord::char('g'); 2]);
+        assert_eq!(parse_keys("Ctrl-r").unwrap(), [Chord::ctrl('r')]);
+        assert_eq!(
+            parse_keys("alt-enter").unwrap(),
+            [Chord::new(KeyCode::Enter, KeyModifiers::ALT)]
+        );
+        assert_eq!(parse_keys("-").unwrap(), [Chord::char('-')]);
+        assert_eq!(parse_keys("shift-K").unwrap(), [Chord::char('K')]);
+        assert!(parse_keys("ctrl-nope").is_err());
+        assert!(parse_keys(" ").is_err());
+    }
+
+    #[test]
+    fn test_format_keys() {
+        assert_eq!(format_keys(&parse_keys("gg").unwrap()), "gg");
+        assert_eq!(format_keys(&parse_keys("ctrl-r").unwrap()), "Ctrl-r");
+        assert_eq!(format_keys(&parse_keys("g enter").unwrap()), "g Enter");
+    }
+
+    #[test]
+    fn test_lookup_sequences() {
+        let keymap = Keymap::default();
+        assert!(keymap.validate().is_ok());
+        assert_eq!(
+            keymap.lookup(&[Chord::char('j')]),
+            Lookup::Action(Action::MoveDown)
+        );
+        assert_eq!(keymap.lookup(&[Chord::char('g')]), Lookup::Pending);
+        assert_eq!(
+            keymap.lookup(&[Chord::char('g'), Chord::char('g')]),
+            Lookup::Action(Action::SelectFirst)
+        );
+        assert_eq!(keymap.lookup(&[Chord::ctrl('j')]), Lookup::Unbound);
+        assert_eq!(keymap.label(Action::Redo).as_deref(), Some("Ctrl-r"));
+    }
+
+    #[test]
+    fn test_every_action_is_listed_and_bound() {
+        let keymap = Keymap::default();
+        for action in Action::ALL {
+            assert!(keymap.label(action).is_some(), "{action:?}");
+        }
+        let mut actions = Action::ALL.to_vec();
+        actions.sort();
+        actions.dedup();
+        assert_eq!(actions.len(), Action::ALL.len());
+    }
+
+    #[test]
+    fn test_overrides_and_conflicts() {
+        let overrides: BTreeMap<Action, Keys> =
+            toml::from_str("undo = [\"ctrl-z\", \"U\"]\nsave = []").unwrap();
+        let keymap = Keymap::default().with_overrides(overrides).unwrap();
+        assert_eq!(
+            keymap.lookup(&[Chord::ctrl('z')]),
+            Lookup::Action(Action::Undo)
+        );
+        assert_eq!(keymap.lookup(&[Chord::char('u')]), Lookup::Unbound);
+        assert_eq!(keymap.label(Action::Save), None);
+
+        // Same key as move_down
+        let overrides = toml::from_str("undo = \"j\"").unwrap();
+        assert!(Keymap::default().with_overrides(overrides).is_err());
+        // Starts the gg sequence
+        let overrides = toml::from_str("undo = \"g\"").unwrap();
+        assert!(Keymap::default().with_overrides(overrides).is_err());
+        // Unknown actions are reported
+        assert!(toml::from_str::<BTreeMap<Action, Keys>>("fly = \"f\"").is_err());
+    }
+}
@@ -1,5 +1,20 @@
+pub mod archive;
+pub mod cli;
+pub mod config;
 pub mod constants;
+pub mod data_file;
+pub mod due;
+pub mod filter;
 pub mod helpers;
+pub mod history;
 pub mod kanban;
+pub mod keymap;
+pub mod migrations;
 pub mod persistence;
+pub mod priority;
+pub mod search;
+pub mod tags;
+pub mod task;
+pub mod theme;
 pub mod widgets;
+pub mod wip;
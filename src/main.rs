@@ -1,11 +1,50 @@
 use color_eyre::Result;
-use kanban::kanban::Kanban;
+use kanban::{
+    archive::AutoArchive,
+    cli::Cli,
+    data_file,
+    kanban::Kanban,
+    keymap::Keymap,
+    persistence::{self, StorageKind},
+    theme::Theme,
+    wip::WipPolicy,
+};
+use ratatui::crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+};
+use std::{io::stdout, panic};
 
 /// Runs the Kanban app. Ratatui makes the heavy lifting for renderization
 fn main() -> Result<()> {
     color_eyre::install()?;
+    let cli = Cli::parse()?;
+    let storage_kind = StorageKind::resolve(cli.storage)?;
+    let data_file = data_file::resolve(cli.data_file, storage_kind)?;
+    let storage_kind = storage_kind.unwrap_or_else(|| StorageKind::from_path(&data_file));
+    let storage = persistence::open(storage_kind, data_file)?;
+
+    let wip_policy = WipPolicy::resolve(cli.wip_policy)?;
+    let keymap = Keymap::resolve(cli.keymap)?;
+    let theme = Theme::resolve(cli.theme)?;
+    let auto_archive = AutoArchive::resolve(cli.archive_after)?;
+
+    let kanban = Kanban::new(storage, cli.board, wip_policy, keymap, theme, auto_archive)?;
     let terminal = ratatui::init();
-    let app_result = Kanban::new()?.run(terminal);
+    enable_mouse()?;
+    let app_result = kanban.run(terminal);
+    let _ = execute!(stdout(), DisableMouseCapture);
     ratatui::restore();
     app_result
 }
+
+/// Turns on mouse events, and makes sure they are turned off again when the app panics
+fn enable_mouse() -> Result<()> {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = execute!(stdout(), DisableMouseCapture);
+        hook(info);
+    }));
+    execute!(stdout(), EnableMouseCapture)?;
+    Ok(())
+}
@@ -0,0 +1,261 @@
+use color_eyre::{Result, eyre::eyre};
+use serde_json::{Map, Value, json};
+
+use crate::persistence::DEFAULT_BOARD;
+
+/// Format version written by this build
+pub const CURRENT_VERSION: u64 = 11;
+
+/// A step that upgrades a document from one version to the next
+type Migration = fn(Value) -> Result<Value>;
+
+/// `MIGRATIONS[n]` upgrades a version `n` document to version `n + 1`
+const MIGRATIONS: [Migration; CURRENT_VERSION as usize] = [
+    v0_to_v1, v1_to_v2, v2_to_v3, v3_to_v4, v4_to_v5, v5_to_v6, v6_to_v7, v7_to_v8, v8_to_v9,
+    v9_to_v10, v10_to_v11,
+];
+
+/// Upgrades a document of any known version to `CURRENT_VERSION`, one step at a time.
+///
+/// Documents written before the `version` field existed are recognised by their shape:
+/// - 0: `{"todo": [..], "doing": [..], "done": [..]}`
+/// - 1: `{"columns": [..]}`, a single board
+/// - 2: `{"boards": [..]}`, the workspace
+///
+/// From version 3 on, every step fills in the board, column and card fields it adds with their
+/// empty value, so the file on disk always spells out the fields of its version. Undo entries
+/// saved without a time get one when they load, see `Entry::at`.
+pub fn migrate(mut document: Value) -> Result<Value> {
+    let mut version = detect_version(&document)?;
+    if version > CURRENT_VERSION {
+        return Err(eyre!(
+            "The data file uses format version {version} but this build only understands up to \
+             version {CURRENT_VERSION}. Please upgrade kanban."
+        ));
+    }
+
+    while version < CURRENT_VERSION {
+        document = MIGRATIONS[version as usize](document)?;
+        version += 1;
+    }
+    Ok(document)
+}
+
+fn detect_version(document: &Value) -> Result<u64> {
+    let object = as_object(document)?;
+    if let Some(version) = object.get("version") {
+        return version
+            .as_u64()
+            .ok_or_else(|| eyre!("Invalid data file version: {version}"));
+    }
+
+    if object.contains_key("boards") {
+        Ok(2)
+    } else if object.contains_key("columns") {
+        Ok(1)
+    } else if ["todo", "doing", "done"]
+        .iter()
+        .all(|key| object.contains_key(*key))
+    {
+        Ok(0)
+    } else {
+        Err(eyre!("Unrecognized data file format"))
+    }
+}
+
+fn as_object(document: &Value) -> Result<&Map<String, Value>> {
+    document
+        .as_object()
+        .ok_or_else(|| eyre!("The data file must contain a JSON object"))
+}
+
+/// The three fixed lists become the first columns of a user defined board
+fn v0_to_v1(document: Value) -> Result<Value> {
+    let object = as_object(&document)?;
+    let columns: Vec<Value> = [("TODO", "todo"), ("Doing", "doing"), ("Done", "done")]
+        .into_iter()
+        .map(|(title, key)| json!({ "title": title, "tasks": object[key] }))
+        .collect();
+    Ok(json!({ "columns": columns }))
+}
+
+/// The single board becomes the default board of a workspace
+fn v1_to_v2(document: Value) -> Result<Value> {
+    let columns = &as_object(&document)?["columns"];
+    Ok(json!({ "boards": [{ "name": DEFAULT_BOARD, "columns": columns }] }))
+}
+
+/// Same layout, now tagged with its version
+fn v2_to_v3(mut document: Value) -> Result<Value> {
+    document["version"] = json!(3);
+    Ok(document)
+}
+
+/// Cards gain tags
+fn v3_to_v4(document: Value) -> Result<Value> {
+    upgrade_tasks(document, 4, |task| default_field(task, "tags", json!([])))
+}
+
+/// Cards gain a priority
+fn v4_to_v5(document: Value) -> Result<Value> {
+    upgrade_tasks(document, 5, |task| {
+        default_field(task, "priority", Value::Null)
+    })
+}
+
+/// Cards gain a due date
+fn v5_to_v6(document: Value) -> Result<Value> {
+    upgrade_tasks(document, 6, |task| default_field(task, "due", Value::Null))
+}
+
+/// Boards keep their undo history
+fn v6_to_v7(document: Value) -> Result<Value> {
+    let history = json!({ "undo": [], "redo": [] });
+    upgrade_boards(document, 7, |board| {
+        default_field(board, "history", history.clone())
+    })
+}
+
+/// Boards keep the color of their tags
+fn v7_to_v8(document: Value) -> Result<Value> {
+    upgrade_boards(document, 8, |board| {
+        default_field(board, "palette", json!({}))
+    })
+}
+
+/// Boards gain an archive and cards remember when they last changed columns
+fn v8_to_v9(document: Value) -> Result<Value> {
+    let document = upgrade_boards(document, 9, |board| {
+        default_field(board, "archive", json!([]))
+    })?;
+    upgrade_tasks(document, 9, |task| {
+        default_field(task, "moved_at", Value::Null)
+    })
+}
+
+/// Columns remember the order their cards are shown in
+fn v9_to_v10(document: Value) -> Result<Value> {
+    upgrade_columns(document, 10, |column| {
+        default_field(column, "sort", json!("manual"))
+    })
+}
+
+/// Columns gain a WIP limit
+fn v10_to_v11(document: Value) -> Result<Value> {
+    upgrade_columns(document, 11, |column| {
+        default_field(column, "limit", Value::Null)
+    })
+}
+
+/// Runs `upgrade` on every board of the workspace and tags the document with `version`
+fn upgrade_boards(
+    mut document: Value,
+    version: u64,
+    mut upgrade: impl FnMut(&mut Map<String, Value>),
+) -> Result<Value> {
+    let boards = document["boards"]
+        .as_array_mut()
+        .ok_or_else(|| eyre!("The data file has no list of boards"))?;
+    for board in boards {
+        match board.as_object_mut() {
+            Some(board) => upgrade(board),
+            None => return Err(eyre!("Invalid board in the data file: {board}")),
+        }
+    }
+    document["version"] = json!(version);
+    Ok(document)
+}
+
+/// Runs `upgrade` on every card of the workspace stored as an object. Cards stored as a bare
+/// title get every field from `Task::new` when they are loaded.
+fn upgrade_tasks(
+    document: Value,
+    version: u64,
+    mut upgrade: impl FnMut(&mut Map<String, Value>),
+) -> Result<Value> {
+    upgrade_columns(document, version, |column| {
+        let Some(tasks) = column.get_mut("tasks").and_then(Value::as_array_mut) else {
+            return;
+        };
+        for task in tasks.iter_mut().filter_map(Value::as_object_mut) {
+            upgrade(task);
+        }
+    })
+}
+
+/// Runs `upgrade` on every column of the workspace
+fn upgrade_columns(
+    document: Value,
+    version: u64,
+    mut upgrade: impl FnMut(&mut Map<String, Value>),
+) -> Result<Value> {
+    upgrade_boards(document, version, |board| {
+        let Some(columns) = board.get_mut("columns").and_then(Value::as_array_mut) else {
+            return;
+        };
+        for column in columns.iter_mut().filter_map(Value::as_object_mut) {
+            upgrade(column);
+        }
+    })
+}
+
+fn default_field(object: &mut Map<String, Value>, field: &str, value: Value) {
+    object.entry(field).or_insert(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_document_is_upgraded() {
+        let legacy = json!({"todo": ["Tarea 1"], "doing": [], "done": ["Tarea 2"]});
+        let upgraded = migrate(legacy).unwrap();
+
+        assert_eq!(upgraded["version"], json!(CURRENT_VERSION));
+        let board = &upgraded["boards"][0];
+        assert_eq!(board["name"], json!(DEFAULT_BOARD));
+        assert_eq!(board["columns"][0]["title"], json!("TODO"));
+        assert_eq!(board["columns"][2]["tasks"], json!(["Tarea 2"]));
+    }
+
+    #[test]
+    fn test_version_3_document_gains_new_fields() {
+        let task = json!({"id": "1", "title": "Tarea 1", "tags": ["bug"]});
+        let v3 = json!({
+            "version": 3,
+            "boards": [{"name": "Work", "columns": [{"title": "TODO", "tasks": [task, "Tarea 2"]}]}]
+        });
+        let upgraded = migrate(v3).unwrap();
+
+        assert_eq!(upgraded["version"], json!(CURRENT_VERSION));
+        let board = &upgraded["boards"][0];
+        assert_eq!(board["history"], json!({"undo": [], "redo": []}));
+        assert_eq!(board["palette"], json!({}));
+        assert_eq!(board["archive"], json!([]));
+        assert_eq!(board["columns"][0]["sort"], json!("manual"));
+        assert_eq!(board["columns"][0]["limit"], Value::Null);
+        let tasks = &board["columns"][0]["tasks"];
+        assert_eq!(tasks[0]["tags"], json!(["bug"]));
+        assert_eq!(tasks[0]["priority"], Value::Null);
+        assert_eq!(tasks[0]["due"], Value::Null);
+        assert_eq!(tasks[0]["moved_at"], Value::Null);
+        assert_eq!(tasks[1], json!("Tarea 2"));
+    }
+
+    #[test]
+    fn test_current_document_is_untouched() {
+        let current = json!({"version": CURRENT_VERSION, "boards": []});
+        assert_eq!(migrate(current.clone()).unwrap(), current);
+    }
+
+    #[test]
+    fn test_newer_and_unknown_documents_fail() {
+        let newer = json!({"version": CURRENT_VERSION + 1, "boards": []});
+        let error = migrate(newer).unwrap_err().to_string();
+        assert!(error.contains("upgrade"));
+
+        assert!(migrate(json!({"cards": []})).is_err());
+        assert!(migrate(json!(["Tarea 1"])).is_err());
+    }
+}
@@ -0,0 +1,265 @@
+use color_eyre::{Result, eyre::eyre};
+use serde::{Deserialize, Serialize};
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+use super::{BoardData, Storage};
+use crate::migrations::{self, CURRENT_VERSION};
+
+/// Keeps the whole workspace in a single JSON file. Every board is saved and loaded on its own.
+#[derive(Debug, Clone)]
+pub struct JsonStorage {
+    /// Location of the workspace file, see `data_file::resolve`
+    path: PathBuf,
+}
+
+/// The whole workspace as stored on disk
+#[derive(Debug, Serialize, Deserialize)]
+struct WorkspaceData {
+    /// Format version, see `migrations`
+    version: u64,
+    boards: Vec<BoardData>,
+}
+
+impl Default for WorkspaceData {
+    fn default() -> Self {
+        WorkspaceData {
+            version: CURRENT_VERSION,
+            boards: Vec::new(),
+        }
+    }
+}
+
+impl JsonStorage {
+    pub fn new(path: PathBuf) -> Self {
+        JsonStorage { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads the workspace file. A missing file is an empty workspace
+    fn read(&self) -> Result<WorkspaceData> {
+        let data = match fs::read_to_string(&self.path) {
+            Ok(data) => data,
+            Err(error) if error.kind() == ErrorKind::NotFound => {
+                return Ok(WorkspaceData::default());
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        parse(&data)
+    }
+
+    /// Writes the workspace file, creating its directory on first run.
+    ///
+    /// The data goes to a temporary file next to the real one, which is synced to disk and then
+    /// renamed over it, so a crash or a full disk never leaves a half written board behind. The
+    /// version being replaced is kept as a `.bak` file.
+    fn write(&self, workspace: &WorkspaceData) -> Result<()> {
+        let dir = self
+            .path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        fs::create_dir_all(dir)?;
+
+        let data = serde_json::to_vec_pretty(workspace)?;
+        let tmp_path = self.sibling("tmp");
+        let mut file = File::create(&tmp_path)?;
+        let written = file.write_all(&data).and_then(|_| file.sync_all());
+        if let Err(error) = written {
+            let _ = fs::remove_file(&tmp_path);
+            return Err(error.into());
+        }
+
+        if self.path.exists() {
+            fs::copy(&self.path, self.sibling("bak"))?;
+        }
+        fs::rename(&tmp_path, &self.path)?;
+
+        // Make the rename itself durable. Not every platform can open a directory
+        if let Ok(dir) = File::open(dir) {
+            let _ = dir.sync_all();
+        }
+        Ok(())
+    }
+
+    /// Path next to the data file with `extension` appended, e.g. `kanban.json.bak`
+    fn sibling(&self, extension: &str) -> PathBuf {
+        let mut path = OsString::from(self.path.as_os_str());
+        path.push(".");
+        path.push(extension);
+        PathBuf::from(path)
+    }
+}
+
+impl Storage for JsonStorage {
+    fn board_names(&self) -> Result<Vec<String>> {
+        let workspace = self.read()?;
+        Ok(workspace
+            .boards
+            .into_iter()
+            .map(|board| board.name)
+            .collect())
+    }
+
+    fn load_board(&self, name: &str) -> Result<Option<BoardData>> {
+        let workspace = self.read()?;
+        Ok(workspace
+            .boards
+            .into_iter()
+            .find(|board| board.name == name))
+    }
+
+    /// Rewrites the file, leaving every other board as it is on disk
+    fn save_board(&self, data: &BoardData) -> Result<()> {
+        let mut workspace = self.read()?;
+
+        match workspace.boards.iter_mut().find(|b| b.name == data.name) {
+            Some(board) => *board = data.clone(),
+            None => workspace.boards.push(data.clone()),
+        }
+
+        self.write(&workspace)
+    }
+
+    fn rename_board(&self, old_name: &str, new_name: &str) -> Result<()> {
+        let mut workspace = self.read()?;
+        if workspace.boards.iter().any(|board| board.name == new_name) {
+            return Err(eyre!("A board called '{new_name}' already exists"));
+        }
+        if let Some(board) = workspace.boards.iter_mut().find(|b| b.name == old_name) {
+            board.name = new_name.to_string();
+            self.write(&workspace)?;
+        }
+        Ok(())
+    }
+
+    fn delete_board(&self, name: &str) -> Result<()> {
+        let mut workspace = self.read()?;
+        workspace.boards.retain(|board| board.name != name);
+        self.write(&workspace)
+    }
+}
+
+/// Parses a workspace document, upgrading it first when it was written by an older version
+fn parse(data: &str) -> Result<WorkspaceData> {
+    let document = migrations::migrate(serde_json::from_str(data)?)?;
+    Ok(serde_json::from_value(document)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        archive::Archive, history::History, tags::Palette, widgets::kanban_column::SortMode,
+    };
+    use crate::{
+        persistence::{ColumnData, DEFAULT_BOARD},
+        task::Task,
+    };
+    use uuid::Uuid;
+
+    fn temp_storage(name: &str) -> JsonStorage {
+        let dir = std::env::temp_dir().join(format!("kanban-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        JsonStorage::new(dir.join("kanban.json"))
+    }
+
+    fn board(tasks: &[&str]) -> BoardData {
+        BoardData {
+            name: "Work".to_string(),
+            columns: vec![ColumnData {
+                title: "TODO".to_string(),
+                tasks: tasks.iter().map(|t| Task::new(t.to_string())).collect(),
+                sort: SortMode::default(),
+                limit: None,
+            }],
+            history: History::default(),
+            palette: Palette::default(),
+            archive: Archive::default(),
+        }
+    }
+
+    #[test]
+    fn test_persist_keeps_backup() {
+        let storage = temp_storage("backup");
+
+        storage.save_board(&board(&[])).unwrap();
+        assert!(!storage.sibling("bak").exists());
+
+        storage.save_board(&board(&["Task 1"])).unwrap();
+
+        let backup = JsonStorage::new(storage.sibling("bak"));
+        let old = backup.existing_board("Work").unwrap();
+        let new = storage.existing_board("Work").unwrap();
+        assert!(old.columns[0].tasks.is_empty());
+        assert_eq!(new.columns[0].tasks.len(), 1);
+        assert!(!storage.sibling("tmp").exists());
+
+        fs::remove_dir_all(storage.path().parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_persist_reports_errors() {
+        let storage = temp_storage("error");
+        // A directory where the file should be makes every write fail
+        fs::create_dir_all(storage.path()).unwrap();
+
+        assert!(storage.save_board(&board(&[])).is_err());
+
+        fs::remove_dir_all(storage.path().parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_task_operations() {
+        let storage = temp_storage("tasks");
+        let data = board(&["Task 1", "Task 2"]);
+        storage.save_board(&data).unwrap();
+
+        let moved = data.columns[0].tasks[1].clone();
+        storage.save_task("Work", 0, 0, &moved).unwrap();
+        storage
+            .delete_task("Work", data.columns[0].tasks[0].id)
+            .unwrap();
+
+        let loaded = storage.existing_board("Work").unwrap();
+        assert_eq!(loaded.columns[0].tasks, vec![moved]);
+        assert!(storage.delete_task("Home", Uuid::new_v4()).is_err());
+
+        fs::remove_dir_all(storage.path().parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_legacy_board_becomes_three_columns() {
+        let legacy = r#"{"todo": ["Tarea 1"], "doing": [], "done": ["Tarea 2"]}"#;
+        let data = parse(legacy).unwrap();
+
+        let board = &data.boards[0];
+        let titles: Vec<_> = board.columns.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(board.name, DEFAULT_BOARD);
+        assert_eq!(titles, ["TODO", "Doing", "Done"]);
+        assert_eq!(board.columns[2].tasks[0].title, "Tarea 2");
+    }
+
+    #[test]
+    fn test_single_board_layout_is_upgraded() {
+        let board = r#"{"columns": [{"title": "Backlog", "tasks": ["Tarea 1"]}]}"#;
+        let data = parse(board).unwrap();
+        assert_eq!(data.version, CURRENT_VERSION);
+        assert_eq!(data.boards[0].columns[0].title, "Backlog");
+    }
+
+    #[test]
+    fn test_workspace_layout_is_upgraded() {
+        let workspace = r#"{"boards": [{"name": "Work", "columns": []}]}"#;
+        let data = parse(workspace).unwrap();
+        assert_eq!(data.boards[0].name, "Work");
+    }
+}
@@ -0,0 +1,254 @@
+mod json;
+mod sqlite;
+
+use color_eyre::{Result, eyre::eyre};
+use serde::{Deserialize, Serialize};
+use std::{env, path::Path, path::PathBuf, str::FromStr};
+use uuid::Uuid;
+
+use crate::{
+    archive::Archive,
+    history::History,
+    tags::Palette,
+    task::Task,
+    widgets::kanban_column::{KanbanColumn, SortMode, loaded_title},
+};
+
+pub use json::JsonStorage;
+pub use sqlite::SqliteStorage;
+
+/// Name given to the board found in files written before the workspace existed
+pub const DEFAULT_BOARD: &str = "Default";
+/// Environment variable used to pick the storage backend when no flag is given
+pub const STORAGE_ENV: &str = "KANBAN_STORAGE";
+
+/// A named board. Boards are listed in the order they were created.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BoardData {
+    pub name: String,
+    pub columns: Vec<ColumnData>,
+    /// Undo history, kept so undo survives restarts
+    #[serde(default)]
+    pub history: History,
+    /// Color of each tag used on the board
+    #[serde(default)]
+    pub palette: Palette,
+    /// Cards taken off the board, see `Archive`
+    #[serde(default)]
+    pub archive: Archive,
+}
+
+/// A column as stored on disk. The order inside `BoardData::columns` is the board order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnData {
+    pub title: String,
+    pub tasks: Vec<Task>,
+    #[serde(default)]
+    pub sort: SortMode,
+    /// WIP limit, the most cards the column should hold
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl From<&KanbanColumn> for ColumnData {
+    fn from(column: &KanbanColumn) -> Self {
+        ColumnData {
+            title: column.title().to_string(),
+            tasks: column.to_json(),
+            sort: column.sort(),
+            limit: column.limit(),
+        }
+    }
+}
+
+impl From<ColumnData> for KanbanColumn {
+    fn from(data: ColumnData) -> Self {
+        let mut column = KanbanColumn::new(loaded_title(data.title));
+        column.load(data.tasks);
+        column.set_sort(data.sort);
+        column.set_limit(data.limit);
+        column
+    }
+}
+
+impl BoardData {
+    pub fn new(
+        name: &str,
+        columns: &[KanbanColumn],
+        history: &History,
+        palette: &Palette,
+        archive: &Archive,
+    ) -> Self {
+        BoardData {
+            name: name.to_string(),
+            columns: columns.iter().map(ColumnData::from).collect(),
+            history: history.clone(),
+            palette: palette.clone(),
+            archive: archive.clone(),
+        }
+    }
+
+    /// Splits the board into the widgets to show, its history, its tag palette and its archive
+    pub fn into_parts(self) -> (Vec<KanbanColumn>, History, Palette, Archive) {
+        let columns = self.columns.into_iter().map(KanbanColumn::from).collect();
+        (columns, self.history, self.palette, self.archive)
+    }
+}
+
+/// Where the boards of the workspace are kept.
+///
+/// Loading and saving whole boards is all a backend must provide. The card and column
+/// operations default to loading the board, changing it and saving it back; backends that can
+/// touch a single record override them.
+pub trait Storage {
+    /// Names of every board in the workspace, in picker order
+    fn board_names(&self) -> Result<Vec<String>>;
+
+    /// Loads the board called `name`. `None` when there is no such board
+    fn load_board(&self, name: &str) -> Result<Option<BoardData>>;
+
+    /// Saves a single board, creating it when needed and leaving every other board untouched
+    fn save_board(&self, board: &BoardData) -> Result<()>;
+
+    /// Fails when a board called `new_name` already exists
+    fn rename_board(&self, old_name: &str, new_name: &str) -> Result<()>;
+
+    fn delete_board(&self, name: &str) -> Result<()>;
+
+    /// Places `task` at `position` inside `column`, moving it there if it's already on the board
+    fn save_task(&self, board: &str, column: usize, position: usize, task: &Task) -> Result<()> {
+        let mut data = self.existing_board(board)?;
+        for column in &mut data.columns {
+            column.tasks.retain(|t| t.id != task.id);
+        }
+        let column = data
+            .columns
+            .get_mut(column)
+            .ok_or_else(|| eyre!("Board '{board}' has no column {column}"))?;
+        let position = position.min(column.tasks.len());
+        column.tasks.insert(position, task.clone());
+        self.save_board(&data)
+    }
+
+    fn delete_task(&self, board: &str, id: Uuid) -> Result<()> {
+        let mut data = self.existing_board(board)?;
+        for column in &mut data.columns {
+            column.tasks.retain(|task| task.id != id);
+        }
+        self.save_board(&data)
+    }
+
+    /// Replaces the column at `position`, or appends it when `position` is the column count
+    fn save_column(&self, board: &str, position: usize, column: &ColumnData) -> Result<()> {
+        let mut data = self.existing_board(board)?;
+        match position.cmp(&data.columns.len()) {
+            std::cmp::Ordering::Less => data.columns[position] = column.clone(),
+            std::cmp::Ordering::Equal => data.columns.push(column.clone()),
+            std::cmp::Ordering::Greater => {
+                return Err(eyre!("Board '{board}' has no column {position}"));
+            }
+        }
+        self.save_board(&data)
+    }
+
+    /// Removes the column at `position` together with its tasks
+    fn delete_column(&self, board: &str, position: usize) -> Result<()> {
+        let mut data = self.existing_board(board)?;
+        if position < data.columns.len() {
+            data.columns.remove(position);
+        }
+        self.save_board(&data)
+    }
+
+    /// Same as `load_board` but a missing board is an error
+    fn existing_board(&self, name: &str) -> Result<BoardData> {
+        self.load_board(name)?
+            .ok_or_else(|| eyre!("There is no board called '{name}'"))
+    }
+}
+
+/// Available storage backends
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum StorageKind {
+    /// A single pretty printed JSON document
+    #[default]
+    Json,
+    /// An embedded SQLite database, one row per card
+    Sqlite,
+}
+
+impl FromStr for StorageKind {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(StorageKind::Json),
+            "sqlite" => Ok(StorageKind::Sqlite),
+            _ => Err(eyre!("Unknown storage '{s}', expected json or sqlite")),
+        }
+    }
+}
+
+impl StorageKind {
+    /// Backend chosen explicitly: the `--storage` flag, then the `KANBAN_STORAGE` variable
+    pub fn resolve(flag: Option<StorageKind>) -> Result<Option<StorageKind>> {
+        match (flag, env::var(STORAGE_ENV)) {
+            (Some(kind), _) => Ok(Some(kind)),
+            (None, Ok(value)) if !value.is_empty() => value.parse().map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Backend matching the extension of the data file, JSON unless it looks like a database
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("db" | "sqlite" | "sqlite3") => StorageKind::Sqlite,
+            _ => StorageKind::Json,
+        }
+    }
+
+    /// File name used in the XDG data directory
+    pub fn default_file_name(&self) -> &'static str {
+        match self {
+            StorageKind::Json => "kanban.json",
+            StorageKind::Sqlite => "kanban.db",
+        }
+    }
+}
+
+/// Opens the workspace at `path` with the given backend
+pub fn open(kind: StorageKind, path: PathBuf) -> Result<Box<dyn Storage>> {
+    let storage: Box<dyn Storage> = match kind {
+        StorageKind::Json => Box::new(JsonStorage::new(path)),
+        StorageKind::Sqlite => Box::new(SqliteStorage::open(&path)?),
+    };
+    Ok(storage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storage_kind_from_path() {
+        assert_eq!(
+            StorageKind::from_path(Path::new("kanban.db")),
+            StorageKind::Sqlite
+        );
+        assert_eq!(
+            StorageKind::from_path(Path::new(".kanban.json")),
+            StorageKind::Json
+        );
+        assert_eq!(
+            "SQLite".parse::<StorageKind>().unwrap(),
+            StorageKind::Sqlite
+        );
+        assert!("csv".parse::<StorageKind>().is_err());
+    }
+
+    #[test]
+    fn test_empty_column_title_loads() {
+        let data: ColumnData = serde_json::from_str(r#"{"title": "", "tasks": []}"#).unwrap();
+        assert_eq!(KanbanColumn::from(data).title(), "Untitled");
+    }
+}
@@ -0,0 +1,491 @@
+use color_eyre::{Result, eyre::eyre};
+use rusqlite::{Connection, OptionalExtension, Transaction, params};
+use serde_json::{Map, Value};
+use std::{collections::HashSet, fs, path::Path};
+use uuid::Uuid;
+
+use super::{BoardData, ColumnData, Storage};
+use crate::task::Task;
+
+/// Schema version, stored in `PRAGMA user_version`
+const SCHEMA_VERSION: i64 = 1;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS boards (
+        name TEXT PRIMARY KEY,
+        position INTEGER NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS columns (
+        board TEXT NOT NULL REFERENCES boards (name) ON UPDATE CASCADE ON DELETE CASCADE,
+        position INTEGER NOT NULL,
+        data TEXT NOT NULL,
+        PRIMARY KEY (board, position)
+    );
+    CREATE TABLE IF NOT EXISTS tasks (
+        id TEXT PRIMARY KEY,
+        board TEXT NOT NULL REFERENCES boards (name) ON UPDATE CASCADE ON DELETE CASCADE,
+        column_position INTEGER NOT NULL,
+        position INTEGER NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS tasks_by_column ON tasks (board, column_position, position);
+";
+
+/// Keeps the workspace in an embedded SQLite database, one row per board, column and card.
+///
+/// Rows hold the JSON form of their record without the nested collections, so new fields don't
+/// need a schema change. Saving a board only writes the rows that actually changed.
+pub struct SqliteStorage {
+    connection: Connection,
+}
+
+impl SqliteStorage {
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        Self::init(Connection::open(path)?)
+    }
+
+    /// A database that lives only as long as the storage. Handy for tests
+    pub fn in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(connection: Connection) -> Result<Self> {
+        let version: i64 = connection.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version > SCHEMA_VERSION {
+            return Err(eyre!(
+                "The database uses schema version {version} but this build only understands up \
+                 to version {SCHEMA_VERSION}. Please upgrade kanban."
+            ));
+        }
+
+        connection.pragma_update(None, "foreign_keys", true)?;
+        connection.execute_batch(SCHEMA)?;
+        connection.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        Ok(SqliteStorage { connection })
+    }
+
+    fn board_exists(tx: &Transaction, board: &str) -> Result<()> {
+        tx.query_row("SELECT 1 FROM boards WHERE name = ?1", [board], |_| Ok(()))
+            .optional()?
+            .ok_or_else(|| eyre!("There is no board called '{board}'"))
+    }
+
+    fn column_count(tx: &Transaction, board: &str) -> Result<usize> {
+        let count: i64 = tx.query_row(
+            "SELECT COUNT(*) FROM columns WHERE board = ?1",
+            [board],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    /// Takes a task out of its column, closing the gap it leaves
+    fn detach_task(tx: &Transaction, board: &str, id: Uuid) -> Result<()> {
+        let place: Option<(i64, i64)> = tx
+            .query_row(
+                "SELECT column_position, position FROM tasks WHERE id = ?1 AND board = ?2",
+                params![id.to_string(), board],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+
+        if let Some((column, position)) = place {
+            tx.execute("DELETE FROM tasks WHERE id = ?1", [id.to_string()])?;
+            tx.execute(
+                "UPDATE tasks SET position = position - 1
+                 WHERE board = ?1 AND column_position = ?2 AND position > ?3",
+                params![board, column, position],
+            )?;
+        }
+        Ok(())
+    }
+
+    fn insert_tasks(tx: &Transaction, board: &str, column: usize, tasks: &[Task]) -> Result<()> {
+        let mut insert = tx.prepare_cached(
+            "INSERT OR REPLACE INTO tasks (id, board, column_position, position, data)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        for (position, task) in tasks.iter().enumerate() {
+            insert.execute(params![
+                task.id.to_string(),
+                board,
+                column as i64,
+                position as i64,
+                serde_json::to_string(task)?
+            ])?;
+        }
+        Ok(())
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn board_names(&self) -> Result<Vec<String>> {
+        let mut query = self
+            .connection
+            .prepare("SELECT name FROM boards ORDER BY position")?;
+        let names = query
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(names)
+    }
+
+    fn load_board(&self, name: &str) -> Result<Option<BoardData>> {
+        let board: Option<String> = self
+            .connection
+            .query_row("SELECT data FROM boards WHERE name = ?1", [name], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        let Some(board) = board else {
+            return Ok(None);
+        };
+
+        let mut columns = Vec::new();
+        let mut query = self
+            .connection
+            .prepare("SELECT data FROM columns WHERE board = ?1 ORDER BY position")?;
+        for column in query.query_map([name], |row| row.get::<_, String>(0))? {
+            let mut column = parse_object(&column?)?;
+            column.insert("tasks".to_string(), Value::Array(Vec::new()));
+            columns.push(Value::Object(column));
+        }
+
+        let mut query = self.connection.prepare(
+            "SELECT column_position, data FROM tasks WHERE board = ?1
+             ORDER BY column_position, position",
+        )?;
+        let tasks = query.query_map([name], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?;
+        for task in tasks {
+            let (column, task) = task?;
+            let tasks = columns
+                .get_mut(column as usize)
+                .and_then(|column| column["tasks"].as_array_mut())
+                .ok_or_else(|| eyre!("Card stored in missing column {column} of '{name}'"))?;
+            tasks.push(serde_json::from_str(&task)?);
+        }
+
+        let mut board = parse_object(&board)?;
+        board.insert("name".to_string(), Value::String(name.to_string()));
+        board.insert("columns".to_string(), Value::Array(columns));
+        Ok(Some(serde_json::from_value(Value::Object(board))?))
+    }
+
+    fn save_board(&self, board: &BoardData) -> Result<()> {
+        let tx = self.connection.unchecked_transaction()?;
+        let name = board.name.as_str();
+
+        tx.execute(
+            "INSERT INTO boards (name, position, data)
+             VALUES (?1, (SELECT COALESCE(MAX(position) + 1, 0) FROM boards), ?2)
+             ON CONFLICT (name) DO UPDATE SET data = excluded.data
+             WHERE data IS NOT excluded.data",
+            params![name, record_without(board, &["name", "columns"])?],
+        )?;
+
+        tx.execute(
+            "DELETE FROM columns WHERE board = ?1 AND position >= ?2",
+            params![name, board.columns.len() as i64],
+        )?;
+        let mut existing: HashSet<String> = {
+            let mut query = tx.prepare("SELECT id FROM tasks WHERE board = ?1")?;
+            query
+                .query_map([name], |row| row.get(0))?
+                .collect::<rusqlite::Result<_>>()?
+        };
+
+        {
+            let mut upsert_column = tx.prepare_cached(
+                "INSERT INTO columns (board, position, data) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (board, position) DO UPDATE SET data = excluded.data
+                 WHERE data IS NOT excluded.data",
+            )?;
+            let mut upsert_task = tx.prepare_cached(
+                "INSERT INTO tasks (id, board, column_position, position, data)
+                 VALUES (?1, ?2, ?3, ?4, ?5)
+                 ON CONFLICT (id) DO UPDATE SET
+                     board = excluded.board,
+                     column_position = excluded.column_position,
+                     position = excluded.position,
+                     data = excluded.data
+                 WHERE board IS NOT excluded.board
+                     OR column_position IS NOT excluded.column_position
+                     OR position IS NOT excluded.position
+                     OR data IS NOT excluded.data",
+            )?;
+
+            for (column_position, column) in board.columns.iter().enumerate() {
+                upsert_column.execute(params![
+                    name,
+                    column_position as i64,
+                    record_without(column, &["tasks"])?
+                ])?;
+
+                for (position, task) in column.tasks.iter().enumerate() {
+                    let id = task.id.to_string();
+                    upsert_task.execute(params![
+                        id,
+                        name,
+                        column_position as i64,
+                        position as i64,
+                        serde_json::to_string(task)?
+                    ])?;
+                    existing.remove(&id);
+                }
+            }
+        }
+
+        // Whatever is left was deleted from the board
+        for id in existing {
+            tx.execute("DELETE FROM tasks WHERE id = ?1", [id])?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn rename_board(&self, old_name: &str, new_name: &str) -> Result<()> {
+        let tx = self.connection.unchecked_transaction()?;
+        let taken = tx
+            .query_row("SELECT 1 FROM boards WHERE name = ?1", [new_name], |_| {
+                Ok(())
+            })
+            .optional()?;
+        if taken.is_some() {
+            return Err(eyre!("A board called '{new_name}' already exists"));
+        }
+        // Columns and cards follow through ON UPDATE CASCADE
+        tx.execute(
+            "UPDATE boards SET name = ?2 WHERE name = ?1",
+            [old_name, new_name],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn delete_board(&self, name: &str) -> Result<()> {
+        self.connection
+            .execute("DELETE FROM boards WHERE name = ?1", [name])?;
+        Ok(())
+    }
+
+    fn save_task(&self, board: &str, column: usize, position: usize, task: &Task) -> Result<()> {
+        let tx = self.connection.unchecked_transaction()?;
+        Self::board_exists(&tx, board)?;
+        if column >= Self::column_count(&tx, board)? {
+            return Err(eyre!("Board '{board}' has no column {column}"));
+        }
+
+        Self::detach_task(&tx, board, task.id)?;
+        let len: i64 = tx.query_row(
+            "SELECT COUNT(*) FROM tasks WHERE board = ?1 AND column_position = ?2",
+            params![board, column as i64],
+            |row| row.get(0),
+        )?;
+        let position = (position as i64).min(len);
+
+        tx.execute(
+            "UPDATE tasks SET position = position + 1
+             WHERE board = ?1 AND column_position = ?2 AND position >= ?3",
+            params![board, column as i64, position],
+        )?;
+        tx.execute(
+            "INSERT OR REPLACE INTO tasks (id, board, column_position, position, data)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                task.id.to_string(),
+                board,
+                column as i64,
+                position,
+                serde_json::to_string(task)?
+            ],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn delete_task(&self, board: &str, id: Uuid) -> Result<()> {
+        let tx = self.connection.unchecked_transaction()?;
+        Self::board_exists(&tx, board)?;
+        Self::detach_task(&tx, board, id)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn save_column(&self, board: &str, position: usize, column: &ColumnData) -> Result<()> {
+        let tx = self.connection.unchecked_transaction()?;
+        Self::board_exists(&tx, board)?;
+        if position > Self::column_count(&tx, board)? {
+            return Err(eyre!("Board '{board}' has no column {position}"));
+        }
+
+        tx.execute(
+            "INSERT OR REPLACE INTO columns (board, position, data) VALUES (?1, ?2, ?3)",
+            params![board, position as i64, record_without(column, &["tasks"])?],
+        )?;
+        tx.execute(
+            "DELETE FROM tasks WHERE board = ?1 AND column_position = ?2",
+            params![board, position as i64],
+        )?;
+        Self::insert_tasks(&tx, board, position, &column.tasks)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn delete_column(&self, board: &str, position: usize) -> Result<()> {
+        let tx = self.connection.unchecked_transaction()?;
+        Self::board_exists(&tx, board)?;
+        let position = position as i64;
+
+        tx.execute(
+            "DELETE FROM columns WHERE board = ?1 AND position = ?2",
+            params![board, position],
+        )?;
+        tx.execute(
+            "DELETE FROM tasks WHERE board = ?1 AND column_position = ?2",
+            params![board, position],
+        )?;
+        // Shift through negative positions so the primary key never collides midway
+        tx.execute(
+            "UPDATE columns SET position = -position WHERE board = ?1 AND position > ?2",
+            params![board, position],
+        )?;
+        tx.execute(
+            "UPDATE columns SET position = -position - 1 WHERE board = ?1 AND position < 0",
+            [board],
+        )?;
+        tx.execute(
+            "UPDATE tasks SET column_position = column_position - 1
+             WHERE board = ?1 AND column_position > ?2",
+            params![board, position],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+/// JSON form of `record` without the given fields, which are stored in their own tables
+fn record_without<T: serde::Serialize>(record: &T, fields: &[&str]) -> Result<String> {
+    let mut value = serde_json::to_value(record)?;
+    if let Some(object) = value.as_object_mut() {
+        for field in fields {
+            object.remove(*field);
+        }
+    }
+    Ok(serde_json::to_string(&value)?)
+}
+
+fn parse_object(data: &str) -> Result<Map<String, Value>> {
+    match serde_json::from_str(data)? {
+        Value::Object(object) => Ok(object),
+        _ => Err(eyre!("Corrupted record in the database: {data}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        archive::{Archive, ArchivedTask},
+        history::History,
+        tags::Palette,
+        widgets::kanban_column::SortMode,
+    };
+
+    fn board(name: &str, columns: &[(&str, &[&str])]) -> BoardData {
+        BoardData {
+            name: name.to_string(),
+            columns: columns
+                .iter()
+                .map(|(title, tasks)| ColumnData {
+                    title: title.to_string(),
+                    tasks: tasks.iter().map(|t| Task::new(t.to_string())).collect(),
+                    sort: SortMode::default(),
+                    limit: None,
+                })
+                .collect(),
+            history: History::default(),
+            palette: Palette::default(),
+            archive: Archive::default(),
+        }
+    }
+
+    #[test]
+    fn test_save_and_load_board() {
+        let storage = SqliteStorage::in_memory().unwrap();
+        let mut data = board("Work", &[("TODO", &["Task 1", "Task 2"]), ("Done", &[])]);
+        storage.save_board(&data).unwrap();
+        storage.save_board(&board("Home", &[])).unwrap();
+
+        // Move a card and drop the other one
+        let task = data.columns[0].tasks.remove(1);
+        data.columns[0].tasks.clear();
+        data.columns[1].tasks.push(task);
+        let archived = Task::new("Task 3".to_string());
+        data.archive.insert(0, ArchivedTask::new(archived, "Done"));
+        storage.save_board(&data).unwrap();
+
+        assert_eq!(storage.load_board("Work").unwrap(), Some(data));
+        assert_eq!(storage.board_names().unwrap(), ["Work", "Home"]);
+        assert_eq!(storage.load_board("Nope").unwrap(), None);
+    }
+
+    #[test]
+    fn test_rename_and_delete_board() {
+        let storage = SqliteStorage::in_memory().unwrap();
+        storage
+            .save_board(&board("Work", &[("TODO", &["Task 1"])]))
+            .unwrap();
+        storage.save_board(&board("Home", &[])).unwrap();
+
+        assert!(storage.rename_board("Work", "Home").is_err());
+        storage.rename_board("Work", "Job").unwrap();
+        let job = storage.existing_board("Job").unwrap();
+        assert_eq!(job.columns[0].tasks[0].title, "Task 1");
+
+        storage.delete_board("Job").unwrap();
+        assert_eq!(storage.board_names().unwrap(), ["Home"]);
+    }
+
+    #[test]
+    fn test_task_and_column_operations() {
+        let storage = SqliteStorage::in_memory().unwrap();
+        let data = board(
+            "Work",
+            &[("TODO", &["Task 1", "Task 2"]), ("Done", &["Task 3"])],
+        );
+        storage.save_board(&data).unwrap();
+
+        // Move "Task 2" to the top of "Done", then delete "Task 1"
+        let task = data.columns[0].tasks[1].clone();
+        storage.save_task("Work", 1, 0, &task).unwrap();
+        storage
+            .delete_task("Work", data.columns[0].tasks[0].id)
+            .unwrap();
+
+        let loaded = storage.existing_board("Work").unwrap();
+        assert!(loaded.columns[0].tasks.is_empty());
+        let titles: Vec<_> = loaded.columns[1].tasks.iter().map(|t| &t.title).collect();
+        assert_eq!(titles, ["Task 2", "Task 3"]);
+
+        storage.delete_column("Work", 0).unwrap();
+        let new_column = ColumnData {
+            title: "Review".to_string(),
+            tasks: Vec::new(),
+            sort: SortMode::default(),
+            limit: None,
+        };
+        storage.save_column("Work", 1, &new_column).unwrap();
+
+        let loaded = storage.existing_board("Work").unwrap();
+        let titles: Vec<_> = loaded.columns.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, ["Done", "Review"]);
+        assert_eq!(loaded.columns[0].tasks.len(), 2);
+        assert!(storage.save_task("Work", 5, 0, &task).is_err());
+    }
+}
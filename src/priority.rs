@@ -0,0 +1,93 @@
+use std::fmt;
+
+use color_eyre::{Result, eyre::eyre};
+use serde::{Deserialize, Serialize};
+
+/// How urgent a card is. `P0` is the most urgent, cards may also have no priority at all
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Priority {
+    P0,
+    P1,
+    P2,
+    P3,
+}
+
+impl Priority {
+    const ALL: [Priority; 4] = [Priority::P0, Priority::P1, Priority::P2, Priority::P3];
+
+    /// Reads the `!1` syntax used in the input box
+    pub fn from_marker(word: &str) -> Option<Self> {
+        let level = word.strip_prefix('!')?;
+        Self::ALL
+            .into_iter()
+            .find(|p| p.level().to_string() == level)
+    }
+
+    /// Reads a priority typed on its own: `P1`, `p1`, `1` or `!1`. Empty or `none` clears it
+    pub fn parse(text: &str) -> Result<Option<Self>> {
+        let text = text.trim();
+        if text.is_empty() || text.eq_ignore_ascii_case("none") {
+            return Ok(None);
+        }
+        let level = text.strip_prefix(['P', 'p', '!']).unwrap_or(text);
+        Self::ALL
+            .into_iter()
+            .find(|p| p.level().to_string() == level)
+            .map(Some)
+            .ok_or_else(|| eyre!("Unknown priority '{text}', expected P0 to P3 or none"))
+    }
+
+    /// One step more urgent. Cards without priority start at the least urgent one
+    pub fn raise(priority: Option<Self>) -> Option<Self> {
+        match priority {
+            None => Some(Priority::P3),
+            Some(p) => Some(Self::ALL[p.level().saturating_sub(1)]),
+        }
+    }
+
+    /// One step less urgent. Lowering the least urgent one removes the priority
+    pub fn lower(priority: Option<Self>) -> Option<Self> {
+        priority.and_then(|p| Self::ALL.get(p.level() + 1).copied())
+    }
+
+    /// Marker written in the input box
+    pub fn marker(self) -> String {
+        format!("!{}", self.level())
+    }
+
+    fn level(self) -> usize {
+        self as usize
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "P{}", self.level())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_priority() {
+        assert_eq!(Priority::from_marker("!1"), Some(Priority::P1));
+        assert_eq!(Priority::from_marker("!9"), None);
+        assert_eq!(Priority::from_marker("1"), None);
+
+        assert_eq!(Priority::parse("p2").unwrap(), Some(Priority::P2));
+        assert_eq!(Priority::parse("0").unwrap(), Some(Priority::P0));
+        assert_eq!(Priority::parse(" none ").unwrap(), None);
+        assert!(Priority::parse("urgent").is_err());
+    }
+
+    #[test]
+    fn test_raise_and_lower() {
+        assert_eq!(Priority::raise(None), Some(Priority::P3));
+        assert_eq!(Priority::raise(Some(Priority::P0)), Some(Priority::P0));
+        assert_eq!(Priority::lower(Some(Priority::P2)), Some(Priority::P3));
+        assert_eq!(Priority::lower(Some(Priority::P3)), None);
+        assert_eq!(Priority::P1.to_string(), "P1");
+    }
+}
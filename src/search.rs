@@ -0,0 +1,83 @@
+use color_eyre::{Result, eyre::eyre};
+use regex::{Regex, RegexBuilder};
+
+use crate::task::Task;
+
+/// Text looked for in the title and description of the cards. Plain queries match any part of
+/// the text ignoring case, queries written between slashes, like `/fix.*bug/`, are regexes
+#[derive(Debug, Clone)]
+pub struct Search {
+    /// Query as written, used to show it again
+    query: String,
+    /// Lowercase query, for plain searches
+    needle: String,
+    regex: Option<Regex>,
+}
+
+impl Search {
+    /// `None` when the query is empty, an error when the regex doesn't compile
+    pub fn parse(query: &str) -> Result<Option<Self>> {
+        if query.is_empty() {
+            return Ok(None);
+        }
+        let regex = match query.strip_prefix('/').and_then(|q| q.strip_suffix('/')) {
+            Some(pattern) if !pattern.is_empty() => Some(
+                RegexBuilder::new(pattern)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|error| eyre!("Invalid regex: {error}"))?,
+            ),
+            _ => None,
+        };
+        Ok(Some(Search {
+            query: query.to_string(),
+            needle: query.to_lowercase(),
+            regex,
+        }))
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn matches(&self, task: &Task) -> bool {
+        [&task.title, &task.description]
+            .into_iter()
+            .any(|text| match &self.regex {
+                Some(regex) => regex.is_match(text),
+                None => text.to_lowercase().contains(&self.needle),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(title: &str, description: &str) -> Task {
+        let mut task = Task::new(title.to_string());
+        task.description = description.to_string();
+        task
+    }
+
+    #[test]
+    fn test_substring_ignores_case() {
+        let search = Search::parse("LOGIN").unwrap().unwrap();
+        assert!(search.matches(&task("Fix login page", "")));
+        assert!(search.matches(&task("Fix page", "The Login button")));
+        assert!(!search.matches(&task("Fix page", "")));
+        assert!(Search::parse("").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_regex_between_slashes() {
+        let search = Search::parse("/^fix .*bug$/").unwrap().unwrap();
+        assert!(search.matches(&task("Fix the BUG", "")));
+        assert!(!search.matches(&task("Please fix the bug", "")));
+        assert!(Search::parse("/(/").is_err());
+
+        // A lone slash is just text
+        let search = Search::parse("/").unwrap().unwrap();
+        assert!(search.matches(&task("and/or", "")));
+    }
+}
@@ -0,0 +1,144 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::take_words;
+
+/// Colors handed out to new tags, in order. Any color ratatui can parse works in the data file
+pub const TAG_COLORS: [&str; 8] = [
+    "blue",
+    "magenta",
+    "cyan",
+    "green",
+    "lightred",
+    "yellow",
+    "lightblue",
+    "lightgreen",
+];
+
+/// Turns `#Bug` or `bug` into `bug`. `None` when nothing valid is left
+pub fn normalize(tag: &str) -> Option<String> {
+    let tag = tag.strip_prefix('#').unwrap_or(tag);
+    let valid = !tag.is_empty()
+        && tag
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '/'));
+    valid.then(|| tag.to_lowercase())
+}
+
+/// Splits the `#tag` words out of `input`. Returns the text left, with the spaces around the
+/// removed words collapsed, and the tags in the order they were written
+pub fn split_tags(input: &str) -> (String, Vec<String>) {
+    let mut tags = Vec::new();
+    let text = take_words(input, |word| {
+        match word.starts_with('#').then(|| normalize(word)).flatten() {
+            Some(tag) => {
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+                true
+            }
+            None => false,
+        }
+    });
+    (text, tags)
+}
+
+/// Tags typed in the tags field, with or without their `#`, each kept once in the order they
+/// were written
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    for tag in input.split_whitespace().filter_map(normalize) {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// Tags written back with their `#`, ready to be edited
+pub fn join_tags(tags: &[String]) -> String {
+    tags.iter()
+        .map(|tag| format!("#{tag}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Color of every tag used on a board. Saved with the board so colors don't change between runs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Palette {
+    colors: BTreeMap<String, String>,
+}
+
+impl Palette {
+    /// Gives a color to the tags that don't have one yet
+    pub fn add(&mut self, tags: &[String]) {
+        for tag in tags {
+            if !self.colors.contains_key(tag) {
+                let color = TAG_COLORS[self.colors.len() % TAG_COLORS.len()];
+                self.colors.insert(tag.clone(), color.to_string());
+            }
+        }
+    }
+
+    pub fn color(&self, tag: &str) -> Color {
+        self.colors
+            .get(tag)
+            .and_then(|color| Color::from_str(color).ok())
+            .unwrap_or(Color::Gray)
+    }
+
+    /// Moves `tag` to the next color of `TAG_COLORS`
+    pub fn cycle(&mut self, tag: &str) {
+        let next = self
+            .colors
+            .get(tag)
+            .and_then(|color| TAG_COLORS.iter().position(|c| c == color))
+            .map_or(0, |i| (i + 1) % TAG_COLORS.len());
+        self.colors
+            .insert(tag.to_string(), TAG_COLORS[next].to_string());
+    }
+
+    /// Every tag with a color, sorted by name
+    pub fn tags(&self) -> impl Iterator<Item = &str> {
+        self.colors.keys().map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_tags() {
+        let (title, tags) = split_tags("Fix #Bug in the #frontend login #bug");
+        assert_eq!(title, "Fix in the login");
+        assert_eq!(tags, ["bug", "frontend"]);
+
+        // A lonely # or an issue number with other symbols is just text
+        let (title, tags) = split_tags("Close # and #12! today");
+        assert_eq!(title, "Close # and #12! today");
+        assert!(tags.is_empty());
+    }
+
+    #[test]
+    fn test_parse_tags_drops_repeats() {
+        assert_eq!(parse_tags("#bug docs #Bug ui docs"), ["bug", "docs", "ui"]);
+        assert!(parse_tags("# !").is_empty());
+    }
+
+    #[test]
+    fn test_palette_assigns_and_cycles_colors() {
+        let mut palette = Palette::default();
+        palette.add(&["bug".to_string(), "docs".to_string()]);
+        assert_eq!(palette.color("bug"), Color::Blue);
+        assert_eq!(palette.color("docs"), Color::Magenta);
+        assert_eq!(palette.color("unknown"), Color::Gray);
+
+        palette.cycle("bug");
+        assert_eq!(palette.color("bug"), Color::Magenta);
+        assert_eq!(palette.tags().collect::<Vec<_>>(), ["bug", "docs"]);
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    due::{parse_due, today},
+    helpers::take_words,
+    priority::Priority,
+    tags::{join_tags, split_tags},
+};
+
+/// A single card of the board. Every card has a stable id so it can be tracked while it moves
+/// between columns, plus free-form metadata for anything the fixed fields don't cover.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Task {
+    pub id: Uuid,
+    pub title: String,
+    pub description: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Last time the card changed columns. `None` until it first moves
+    pub moved_at: Option<DateTime<Utc>>,
+    /// Lowercase labels without the `#`, in the order they were added
+    pub tags: Vec<String>,
+    pub priority: Option<Priority>,
+    /// Day the card is due, in local time
+    pub due: Option<NaiveDate>,
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl Task {
+    pub fn new(title: String) -> Self {
+        let now = Utc::now();
+        Task {
+            id: Uuid::new_v4(),
+            title,
+            description: String::new(),
+            created_at: now,
+            updated_at: now,
+            moved_at: None,
+            tags: Vec::new(),
+            priority: None,
+            due: None,
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    /// A task created from what the user typed, see `set_input`
+    pub fn from_input(input: &str) -> Self {
+        let mut task = Task::new(String::new());
+        task.set_input(input);
+        task
+    }
+
+    /// Takes the title, the `!1` priority, the `@fri` due date and the `#tag` words from
+    /// `input`. A title made only of markers is kept as written
+    pub fn set_input(&mut self, input: &str) {
+        let today = today();
+        let mut priority = None;
+        let mut due = None;
+        let text = take_words(input, |word| {
+            if let Some(marker) = Priority::from_marker(word) {
+                priority = Some(marker);
+                return true;
+            }
+            match word
+                .strip_prefix('@')
+                .and_then(|date| parse_due(date, today))
+            {
+                Some(date) => {
+                    due = Some(date);
+                    true
+                }
+                None => false,
+            }
+        });
+        let (title, tags) = split_tags(&text);
+        if title.is_empty() {
+            self.title = input.to_string();
+        } else {
+            self.title = title;
+            self.tags = tags;
+            self.priority = priority;
+            self.due = due;
+        }
+    }
+
+    /// The title followed by the priority and the tags, in the syntax `set_input` reads
+    pub fn input(&self) -> String {
+        let mut input = self.title.clone();
+        if let Some(priority) = self.priority {
+            input = format!("{input} {}", priority.marker());
+        }
+        if let Some(due) = self.due {
+            input = format!("{input} @{}", due.format("%Y-%m-%d"));
+        }
+        if !self.tags.is_empty() {
+            input = format!("{input} {}", join_tags(&self.tags));
+        }
+        input
+    }
+
+    /// Marks the task as modified right now
+    pub fn touch(&mut self) {
+        self.updated_at = Utc::now();
+    }
+}
+
+/// On-disk representation of a task. Older files store every card as a bare string, so both
+/// shapes are accepted when reading.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TaskRepr {
+    Legacy(String),
+    Full {
+        id: Uuid,
+        title: String,
+        #[serde(default)]
+        description: String,
+        created_at: DateTime<Utc>,
+        updated_at: DateTime<Utc>,
+        #[serde(default)]
+        moved_at: Option<DateTime<Utc>>,
+        #[serde(default)]
+        tags: Vec<String>,
+        #[serde(default)]
+        priority: Option<Priority>,
+        #[serde(default)]
+        due: Option<NaiveDate>,
+        #[serde(default)]
+        metadata: BTreeMap<String, String>,
+    },
+}
+
+impl<'de> Deserialize<'de> for Task {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let task = match TaskRepr::deserialize(deserializer)? {
+            TaskRepr::Legacy(title) => Task::new(title),
+            TaskRepr::Full {
+                id,
+                title,
+                description,
+                created_at,
+                updated_at,
+                moved_at,
+                tags,
+                priority,
+                due,
+                metadata,
+            } => Task {
+                id,
+                title,
+                description,
+                created_at,
+                updated_at,
+                moved_at,
+                tags,
+                priority,
+                due,
+                metadata,
+            },
+        };
+        Ok(task)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_task_has_unique_id() {
+        let first = Task::new("Task 1".to_string());
+        let second = Task::new("Task 1".to_string());
+        assert_ne!(first.id, second.id);
+        assert_eq!(first.created_at, first.updated_at);
+    }
+
+    #[test]
+    fn test_load_legacy_string() {
+        let task: Task = serde_json::from_str("\"Tarea 1\"").unwrap();
+        assert_eq!(task.title, "Tarea 1");
+        assert!(task.description.is_empty());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut task = Task::new("Task 1".to_string());
+        task.metadata
+            .insert("owner".to_string(), "pedro".to_string());
+
+        let json = serde_json::to_string(&task).unwrap();
+        let loaded: Task = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, task);
+    }
+
+    #[test]
+    fn test_input_syntax() {
+        let mut task = Task::from_input("Fix login #bug #frontend");
+        assert_eq!(task.title, "Fix login");
+        assert_eq!(task.tags, ["bug", "frontend"]);
+        assert_eq!(task.input(), "Fix login #bug #frontend");
+
+        task.set_input("#bug");
+        assert_eq!(task.title, "#bug");
+        assert_eq!(task.tags, ["bug", "frontend"]);
+
+        task.set_input("Fix login !1 #bug @2026-11-03");
+        assert_eq!(task.priority, Some(Priority::P1));
+        assert_eq!(task.due, NaiveDate::from_ymd_opt(2026, 11, 3));
+        assert_eq!(task.input(), "Fix login !1 @2026-11-03 #bug");
+
+        // Unknown dates are part of the title
+        task.set_input("Email @someone");
+        assert_eq!(task.title, "Email @someone");
+        assert_eq!(task.due, None);
+        task.set_input("Call mom @+3é");
+        assert_eq!(task.title, "Call mom @+3é");
+    }
+}
@@ -0,0 +1,276 @@
+use color_eyre::{Result, eyre::eyre};
+use ratatui::style::{Color, Modifier, Style};
+use serde::{Deserialize, Serialize};
+use std::{env, path::PathBuf};
+
+use crate::{config, due::Urgency, priority::Priority};
+
+/// Environment variable naming the theme when no flag is given
+pub const THEME_ENV: &str = "KANBAN_THEME";
+/// Themes that come with the app, `dark` being the default one
+pub const BUILT_IN_THEMES: [&str; 4] = ["dark", "light", "high-contrast", "monochrome"];
+
+/// Colors of every part of the app. `reset` stands for the terminal default color
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Theme {
+    /// Borders of the columns
+    pub border: Color,
+    /// Border and title of the focused column
+    pub focused: Color,
+    /// Text of the cards
+    pub text: Color,
+    /// Text of the selected card
+    pub selected: Color,
+    /// Background of the cards matching the search
+    pub search_match: Color,
+    /// Border of the columns holding more cards than their WIP limit
+    pub over_limit: Color,
+    /// Markers of P0 to P3
+    pub priorities: [Color; 4],
+    pub overdue: Color,
+    pub due_soon: Color,
+    pub due_later: Color,
+    /// Text drawn on top of the tag colors
+    pub tag_text: Color,
+    /// Whether tags use the colors of the board. Without them tags are shown in reverse video
+    pub tag_colors: bool,
+    /// Borders of the popups
+    pub popup: Color,
+    /// Lists and labels inside the popups
+    pub popup_text: Color,
+    /// Text typed in the input box
+    pub input: Color,
+    /// Messages like "Saved" in the footer
+    pub info: Color,
+    pub error: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Theme {
+            border: Color::Reset,
+            focused: Color::Cyan,
+            text: Color::Yellow,
+            selected: Color::White,
+            search_match: Color::DarkGray,
+            over_limit: Color::Red,
+            priorities: [Color::Red, Color::LightRed, Color::LightYellow, Color::Blue],
+            overdue: Color::Red,
+            due_soon: Color::LightYellow,
+            due_later: Color::Gray,
+            tag_text: Color::Black,
+            tag_colors: true,
+            popup: Color::Reset,
+            popup_text: Color::Yellow,
+            input: Color::Red,
+            info: Color::Green,
+            error: Color::Red,
+        }
+    }
+
+    /// For terminals with a light background
+    pub fn light() -> Self {
+        Theme {
+            border: Color::DarkGray,
+            focused: Color::Blue,
+            text: Color::Black,
+            selected: Color::Blue,
+            search_match: Color::LightYellow,
+            over_limit: Color::Red,
+            priorities: [Color::Red, Color::Magenta, Color::Blue, Color::DarkGray],
+            overdue: Color::Red,
+            due_soon: Color::Magenta,
+            due_later: Color::DarkGray,
+            tag_text: Color::Black,
+            tag_colors: true,
+            popup: Color::DarkGray,
+            popup_text: Color::Blue,
+            input: Color::Black,
+            info: Color::Green,
+            error: Color::Red,
+        }
+    }
+
+    /// Bright colors only, on the terminal background
+    pub fn high_contrast() -> Self {
+        Theme {
+            border: Color::White,
+            focused: Color::LightCyan,
+            text: Color::White,
+            selected: Color::LightYellow,
+            search_match: Color::Blue,
+            over_limit: Color::LightRed,
+            priorities: [
+                Color::LightRed,
+                Color::LightMagenta,
+                Color::LightYellow,
+                Color::LightCyan,
+            ],
+            overdue: Color::LightRed,
+            due_soon: Color::LightYellow,
+            due_later: Color::White,
+            tag_text: Color::Black,
+            tag_colors: true,
+            popup: Color::White,
+            popup_text: Color::White,
+            input: Color::LightYellow,
+            info: Color::LightGreen,
+            error: Color::LightRed,
+        }
+    }
+
+    /// No colors at all, used when `NO_COLOR` is set
+    pub fn monochrome() -> Self {
+        Theme {
+            border: Color::Reset,
+            focused: Color::Reset,
+            text: Color::Reset,
+            selected: Color::Reset,
+            search_match: Color::Reset,
+            over_limit: Color::Reset,
+            priorities: [Color::Reset; 4],
+            overdue: Color::Reset,
+            due_soon: Color::Reset,
+            due_later: Color::Reset,
+            tag_text: Color::Reset,
+            tag_colors: false,
+            popup: Color::Reset,
+            popup_text: Color::Reset,
+            input: Color::Reset,
+            info: Color::Reset,
+            error: Color::Reset,
+        }
+    }
+
+    pub fn built_in(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Theme::dark()),
+            "light" => Some(Theme::light()),
+            "high-contrast" => Some(Theme::high_contrast()),
+            "monochrome" => Some(Theme::monochrome()),
+            _ => None,
+        }
+    }
+
+    /// Theme named by the `--theme` flag or the `KANBAN_THEME` variable, then monochrome when
+    /// `NO_COLOR` is set, then `theme.toml` in the config directory, and `dark` otherwise.
+    /// Names that aren't built in are read from `themes/<name>.toml` in the config directory
+    pub fn resolve(flag: Option<String>) -> Result<Theme> {
+        let name = flag
+            .or_else(|| env::var(THEME_ENV).ok())
+            .filter(|name| !name.is_empty());
+        let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        match name {
+            Some(name) => Theme::named(&name),
+            None if no_color => Ok(Theme::monochrome()),
+            None => match config::find("theme") {
+                Some(path) => Theme::load(path),
+                None => Ok(Theme::default()),
+            },
+        }
+    }
+
+    fn named(name: &str) -> Result<Theme> {
+        if let Some(theme) = Theme::built_in(name) {
+            return Ok(theme);
+        }
+        let path = config::find(&format!("themes/{name}")).ok_or_else(|| {
+            eyre!(
+                "Unknown theme '{name}', expected one of {} or a file in the themes directory",
+                BUILT_IN_THEMES.join(", ")
+            )
+        })?;
+        Theme::load(path)
+    }
+
+    /// Reads a user theme. It starts from the theme named by its `base` key, `dark` by default,
+    /// and replaces only the colors it lists
+    fn load(path: PathBuf) -> Result<Theme> {
+        let serde_json::Value::Object(mut user) = config::read(&path)? else {
+            return Err(eyre!("Invalid theme in {}", path.display()));
+        };
+        let base = match user.remove("base") {
+            Some(serde_json::Value::String(base)) => Theme::built_in(&base)
+                .ok_or_else(|| eyre!("Unknown base theme '{base}' in {}", path.display()))?,
+            Some(_) => return Err(eyre!("The base of {} must be a name", path.display())),
+            None => Theme::default(),
+        };
+        Theme::with_overrides(base, user)
+            .map_err(|error| eyre!("Invalid theme in {}: {error}", path.display()))
+    }
+
+    fn with_overrides(
+        base: Theme,
+        overrides: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<Theme> {
+        let serde_json::Value::Object(mut theme) = serde_json::to_value(base)? else {
+            unreachable!("themes serialize to objects");
+        };
+        for (key, value) in overrides {
+            if !theme.contains_key(&key) {
+                return Err(eyre!("unknown color '{key}'"));
+            }
+            theme.insert(key, value);
+        }
+        Ok(serde_json::from_value(serde_json::Value::Object(theme))?)
+    }
+
+    pub fn priority(&self, priority: Priority) -> Color {
+        self.priorities[priority as usize]
+    }
+
+    pub fn urgency(&self, urgency: Urgency) -> Color {
+        match urgency {
+            Urgency::Overdue => self.overdue,
+            Urgency::Soon => self.due_soon,
+            Urgency::Later => self.due_later,
+        }
+    }
+
+    /// Chip of a tag whose color in the board palette is `color`
+    pub fn tag(&self, color: Color) -> Style {
+        if self.tag_colors {
+            Style::new().fg(self.tag_text).bg(color)
+        } else {
+            Style::new().add_modifier(Modifier::REVERSED)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_built_in_themes() {
+        for name in BUILT_IN_THEMES {
+            assert!(Theme::built_in(name).is_some(), "{name}");
+        }
+        assert_eq!(Theme::built_in("solarized"), None);
+        assert_eq!(Theme::dark().priority(Priority::P0), Color::Red);
+        assert_eq!(
+            Theme::monochrome().tag(Color::Blue),
+            Style::new().add_modifier(Modifier::REVERSED)
+        );
+    }
+
+    #[test]
+    fn test_user_theme_overrides_its_base() {
+        let overrides = serde_json::from_str(r##"{"text": "green", "overdue": "#ff0000"}"##);
+        let theme = Theme::with_overrides(Theme::light(), overrides.unwrap()).unwrap();
+        assert_eq!(theme.text, Color::Green);
+        assert_eq!(theme.overdue, Color::Rgb(255, 0, 0));
+        assert_eq!(theme.focused, Theme::light().focused);
+
+        let overrides = serde_json::from_str(r#"{"txt": "green"}"#).unwrap();
+        assert!(Theme::with_overrides(Theme::dark(), overrides).is_err());
+        let overrides = serde_json::from_str(r#"{"text": "not a color"}"#).unwrap();
+        assert!(Theme::with_overrides(Theme::dark(), overrides).is_err());
+    }
+}
@@ -0,0 +1,132 @@
+use chrono::Local;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, Clear, HighlightSpacing, List, ListState, Paragraph, StatefulWidget, Widget},
+};
+
+use crate::{archive::Archive, constants::HIGHLIGHT_SIMBOL, search::Search, theme::Theme};
+
+/// Popup listing the archived cards of the board, optionally narrowed down by a search
+#[derive(Debug, Default)]
+pub struct ArchiveView {
+    /// Indexes inside the archive of the listed cards
+    rows: Vec<usize>,
+    state: ListState,
+    search: Option<Search>,
+}
+
+impl ArchiveView {
+    /// Lists the cards of `archive` matching the search, keeping the selection on the same row
+    pub fn load(&mut self, archive: &Archive) {
+        self.rows = archive.search(self.search.as_ref());
+        let row = self.state.selected().unwrap_or(0);
+        let last = self.rows.len().checked_sub(1);
+        self.state.select(last.map(|last| row.min(last)));
+    }
+
+    pub fn search(&self) -> Option<&Search> {
+        self.search.as_ref()
+    }
+
+    /// Replaces the search and goes back to the first card. Call `load` afterwards
+    pub fn set_search(&mut self, search: Option<Search>) {
+        self.search = search;
+        self.state.select(Some(0));
+    }
+
+    /// Index inside the archive of the selected card
+    pub fn selected(&self) -> Option<usize> {
+        self.state
+            .selected()
+            .and_then(|row| self.rows.get(row).copied())
+    }
+
+    pub fn select_next(&mut self) {
+        self.state.select_next()
+    }
+
+    pub fn select_previous(&mut self) {
+        self.state.select_previous()
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, archive: &Archive, theme: &Theme) {
+        let title = match &self.search {
+            Some(search) => format!(
+                "Archive, {} of {} match '{}'",
+                self.rows.len(),
+                archive.len(),
+                search.query()
+            ),
+            None => format!("Archive, {} cards", archive.len()),
+        };
+        let block = Block::bordered()
+            .title(Line::raw(title).centered())
+            .border_style(theme.popup);
+        Widget::render(Clear, area, buf);
+
+        if self.rows.is_empty() {
+            let placeholder = if archive.is_empty() {
+                "No archived cards"
+            } else {
+                "No archived card matches the search"
+            };
+            Paragraph::new(Line::raw(placeholder).dim().centered())
+                .block(block)
+                .render(area, buf);
+            return;
+        }
+
+        let items = self
+            .rows
+            .iter()
+            .filter_map(|i| archive.get(*i))
+            .map(|card| {
+                let title = card.task.title.lines().next().unwrap_or_default();
+                let archived_at = card.archived_at.with_timezone(&Local).format("%Y-%m-%d");
+                Line::from(vec![
+                    Span::raw(title.to_string()),
+                    Span::raw(format!("  {} · {archived_at}", card.column)).dim(),
+                ])
+            });
+        let list = List::new(items)
+            .block(block)
+            .highlight_symbol(HIGHLIGHT_SIMBOL)
+            .highlight_style(theme.selected)
+            .fg(theme.popup_text)
+            .highlight_spacing(HighlightSpacing::Always);
+
+        StatefulWidget::render(list, area, buf, &mut self.state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{archive::ArchivedTask, task::Task};
+
+    #[test]
+    fn test_search_narrows_the_list() {
+        let mut archive = Archive::default();
+        for title in ["Fix login", "Write docs", "Fix signup"] {
+            archive.insert(0, ArchivedTask::new(Task::new(title.to_string()), "Done"));
+        }
+        let mut view = ArchiveView::default();
+        view.load(&archive);
+        view.select_next();
+        assert_eq!(view.selected(), Some(1));
+
+        view.set_search(Search::parse("fix").unwrap());
+        view.load(&archive);
+        assert_eq!(view.selected(), Some(0));
+        view.select_next();
+        assert_eq!(view.selected(), Some(2));
+
+        // Restoring the last match leaves the selection on the one before
+        archive.remove(2);
+        view.load(&archive);
+        assert_eq!(view.selected(), Some(0));
+    }
+}
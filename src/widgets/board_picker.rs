@@ -0,0 +1,55 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Stylize,
+    text::Line,
+    widgets::{Block, Clear, HighlightSpacing, List, ListState, StatefulWidget, Widget},
+};
+
+use crate::{constants::HIGHLIGHT_SIMBOL, theme::Theme};
+
+/// Popup listing every board of the workspace so the user can pick one
+#[derive(Debug, Default)]
+pub struct BoardPicker {
+    boards: Vec<String>,
+    state: ListState,
+}
+
+impl BoardPicker {
+    /// Replaces the listed boards and selects `active` when it is present
+    pub fn load(&mut self, boards: Vec<String>, active: &str) {
+        let selected = boards.iter().position(|board| board == active);
+        self.boards = boards;
+        self.state.select(selected.or(Some(0)));
+    }
+
+    pub fn selected_board(&self) -> Option<&str> {
+        self.state
+            .selected()
+            .and_then(|i| self.boards.get(i))
+            .map(String::as_str)
+    }
+
+    pub fn select_next(&mut self) {
+        self.state.select_next()
+    }
+
+    pub fn select_previous(&mut self) {
+        self.state.select_previous()
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let block = Block::bordered()
+            .title(Line::raw("Boards").centered())
+            .border_style(theme.popup);
+        let list = List::new(self.boards.iter().map(String::as_str))
+            .block(block)
+            .highlight_symbol(HIGHLIGHT_SIMBOL)
+            .highlight_style(theme.selected)
+            .fg(theme.popup_text)
+            .highlight_spacing(HighlightSpacing::Always);
+
+        Widget::render(Clear, area, buf);
+        StatefulWidget::render(list, area, buf, &mut self.state);
+    }
+}
@@ -0,0 +1,199 @@
+use chrono::{DateTime, Local, Utc};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph, Widget, Wrap},
+};
+
+use crate::{
+    constants::HIGHLIGHT_SIMBOL,
+    due::{Urgency, today, urgency},
+    tags::Palette,
+    task::Task,
+    theme::Theme,
+};
+
+/// Editable fields of a card, in the order they are shown
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum DetailField {
+    #[default]
+    Title,
+    Priority,
+    Due,
+    Tags,
+    Description,
+}
+
+impl DetailField {
+    pub fn next(self) -> Self {
+        match self {
+            DetailField::Title => DetailField::Priority,
+            DetailField::Priority => DetailField::Due,
+            DetailField::Due => DetailField::Tags,
+            DetailField::Tags | DetailField::Description => DetailField::Description,
+        }
+    }
+
+    pub fn previous(self) -> Self {
+        match self {
+            DetailField::Title | DetailField::Priority => DetailField::Title,
+            DetailField::Due => DetailField::Priority,
+            DetailField::Tags => DetailField::Due,
+            DetailField::Description => DetailField::Tags,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DetailField::Title => "Title",
+            DetailField::Priority => "Priority",
+            DetailField::Due => "Due",
+            DetailField::Tags => "Tags",
+            DetailField::Description => "Description",
+        }
+    }
+}
+
+/// Popup showing everything about a card: full text, timestamps and recent changes
+pub struct CardDetail<'a> {
+    task: &'a Task,
+    palette: &'a Palette,
+    theme: &'a Theme,
+    /// Field highlighted for editing
+    field: DetailField,
+    /// Time and summary of the changes made to the card, oldest first
+    activity: Vec<(DateTime<Utc>, String)>,
+}
+
+impl<'a> CardDetail<'a> {
+    pub fn new(
+        task: &'a Task,
+        palette: &'a Palette,
+        theme: &'a Theme,
+        field: DetailField,
+        activity: Vec<(DateTime<Utc>, String)>,
+    ) -> Self {
+        CardDetail {
+            task,
+            palette,
+            theme,
+            field,
+            activity,
+        }
+    }
+
+    /// Label of `field`, marked when it is the one selected
+    fn label(&self, field: DetailField) -> Line<'static> {
+        if field == self.field {
+            Line::styled(
+                format!("{HIGHLIGHT_SIMBOL}{}", field.label()),
+                Style::new()
+                    .fg(self.theme.selected)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            Line::styled(format!("  {}", field.label()), self.theme.popup_text)
+        }
+    }
+}
+
+fn local_time(time: DateTime<Utc>) -> String {
+    time.with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+impl Widget for CardDetail<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut lines = vec![self.label(DetailField::Title)];
+        lines.extend(
+            self.task
+                .title
+                .lines()
+                .map(|line| Line::raw(line.to_string())),
+        );
+        lines.push(Line::default());
+
+        lines.push(self.label(DetailField::Priority));
+        match self.task.priority {
+            Some(priority) => lines.push(Line::styled(
+                priority.to_string(),
+                self.theme.priority(priority),
+            )),
+            None => lines.push(Line::raw("No priority").dim()),
+        }
+        lines.push(Line::default());
+
+        lines.push(self.label(DetailField::Due));
+        let today = today();
+        match self.task.due {
+            Some(due) => {
+                let date = due.format("%a %Y-%m-%d");
+                let urgency = urgency(due, today);
+                let text = match urgency {
+                    Urgency::Overdue => format!("{date}, overdue"),
+                    Urgency::Soon => format!("{date}, due soon"),
+                    Urgency::Later => date.to_string(),
+                };
+                lines.push(Line::styled(text, self.theme.urgency(urgency)));
+            }
+            None => lines.push(Line::raw("No due date").dim()),
+        }
+        lines.push(Line::default());
+
+        lines.push(self.label(DetailField::Tags));
+        if self.task.tags.is_empty() {
+            lines.push(Line::raw("No tags").dim());
+        } else {
+            let mut chips = Vec::new();
+            for tag in &self.task.tags {
+                chips.push(Span::styled(
+                    format!(" {tag} "),
+                    self.theme.tag(self.palette.color(tag)),
+                ));
+                chips.push(Span::raw(" "));
+            }
+            lines.push(Line::from(chips));
+        }
+        lines.push(Line::default());
+
+        lines.push(self.label(DetailField::Description));
+        if self.task.description.is_empty() {
+            lines.push(Line::raw("No description").dim());
+        } else {
+            lines.extend(
+                self.task
+                    .description
+                    .lines()
+                    .map(|line| Line::raw(line.to_string())),
+            );
+        }
+        lines.push(Line::default());
+
+        lines.push(Line::raw(format!(
+            "Created {}, updated {}",
+            local_time(self.task.created_at),
+            local_time(self.task.updated_at)
+        )));
+        lines.push(Line::default());
+
+        lines.push(Line::styled("Activity", self.theme.popup_text));
+        if self.activity.is_empty() {
+            lines.push(Line::raw("No recent changes").dim());
+        }
+        for (at, summary) in &self.activity {
+            lines.push(Line::raw(format!("{}  {summary}", local_time(*at))));
+        }
+
+        let detail = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+            Block::bordered()
+                .title(Line::raw("Card").centered())
+                .border_style(self.theme.popup),
+        );
+
+        Widget::render(Clear, area, buf);
+        Widget::render(detail, area, buf);
+    }
+}
@@ -0,0 +1,51 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph, Widget, Wrap},
+};
+
+use crate::{
+    constants::{CONFIRM_NO, CONFIRM_YES},
+    theme::Theme,
+};
+
+/// Yes/no popup asked before something destructive
+pub struct Confirm<'a> {
+    question: &'a str,
+    theme: &'a Theme,
+}
+
+impl<'a> Confirm<'a> {
+    pub fn new(question: &'a str, theme: &'a Theme) -> Self {
+        Confirm { question, theme }
+    }
+}
+
+impl Widget for Confirm<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let answers = Line::from(vec![
+            Span::raw(CONFIRM_YES.to_string()).bold(),
+            Span::raw(" yes   "),
+            Span::raw(CONFIRM_NO.to_string()).bold(),
+            Span::raw(" no"),
+        ]);
+        let lines = vec![
+            Line::raw(self.question).fg(self.theme.popup_text),
+            Line::default(),
+            answers,
+        ];
+        let confirm = Paragraph::new(lines)
+            .centered()
+            .wrap(Wrap { trim: true })
+            .block(
+                Block::bordered()
+                    .title(Line::raw("Confirm").centered())
+                    .border_style(self.theme.popup),
+            );
+
+        Widget::render(Clear, area, buf);
+        Widget::render(confirm, area, buf);
+    }
+}
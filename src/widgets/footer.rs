@@ -1,28 +1,92 @@
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
+    style::Stylize,
+    text::{Line, Span},
     widgets::{Paragraph, Widget},
 };
 
-use crate::constants::{
-    DOING_LIST, DONE_LIST, MOVE_DOWN, MOVE_TO_DOING, MOVE_TO_DONE, MOVE_TO_TODO, MOVE_UP, TODO_LIST,
-};
+use crate::{theme::Theme, widgets::help::KeyHelp};
+
+/// Message shown in the footer instead of the key hints
+#[derive(Debug, Clone, PartialEq)]
+pub enum Status {
+    /// Tells something was done, like "Saved". Goes away on its own
+    Info(String),
+    /// Stays until the next key press
+    Error(String),
+}
+
+/// Bottom line of the app. Shows the keys that matter right now, or the last message when
+/// there is one
+pub struct Footer<'a> {
+    status: Option<&'a Status>,
+    hints: Vec<KeyHelp>,
+    theme: &'a Theme,
+}
 
-pub struct Footer;
+impl<'a> Footer<'a> {
+    pub fn new(status: Option<&'a Status>, hints: Vec<KeyHelp>, theme: &'a Theme) -> Self {
+        Footer {
+            status,
+            hints,
+            theme,
+        }
+    }
 
-impl Widget for Footer {
+    /// Keys in bold, each followed by what it does
+    fn hints(&self) -> Line<'static> {
+        let mut spans = Vec::new();
+        for (i, (keys, text)) in self.hints.iter().enumerate() {
+            if i > 0 {
+                spans.push(Span::raw(", "));
+            }
+            spans.push(Span::raw(keys.clone()).bold());
+            spans.push(Span::raw(format!(" {text}")));
+        }
+        Line::from(spans)
+    }
+}
+
+impl Widget for Footer<'_> {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let message = format!(
-            "Use {}/{} to move, {}/{}/{} to navigate lists, {}/{}/{} move items.",
-            MOVE_DOWN,
-            MOVE_UP,
-            TODO_LIST,
-            DOING_LIST,
-            DONE_LIST,
-            MOVE_TO_TODO,
-            MOVE_TO_DOING,
-            MOVE_TO_DONE
-        );
-        Paragraph::new(message).centered().render(area, buf);
+        let (message, color) = match self.status {
+            Some(Status::Info(message)) => (message, self.theme.info),
+            Some(Status::Error(message)) => (message, self.theme.error),
+            None => {
+                Paragraph::new(self.hints()).centered().render(area, buf);
+                return;
+            }
+        };
+        Paragraph::new(message.as_str())
+            .style(color)
+            .centered()
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hints_line() {
+        let theme = Theme::default();
+        let hints = vec![("j/k".to_string(), "move"), ("?".to_string(), "help")];
+        let footer = Footer::new(None, hints, &theme);
+        assert_eq!(footer.hints().to_string(), "j/k move, ? help");
+    }
+
+    #[test]
+    fn test_status_replaces_hints() {
+        let theme = Theme::default();
+        let area = Rect::new(0, 0, 20, 1);
+        let mut buf = Buffer::empty(area);
+        let status = Status::Info("Saved".to_string());
+        let hints = vec![("?".to_string(), "help")];
+        Footer::new(Some(&status), hints, &theme).render(area, &mut buf);
+        let line: String = buf.content().iter().map(|cell| cell.symbol()).collect();
+        assert_eq!(line.trim(), "Saved");
+        assert_eq!(buf[(10, 0)].fg, theme.info);
     }
 }
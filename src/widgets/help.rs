@@ -0,0 +1,234 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, Clear, Paragraph, Widget},
+};
+
+use crate::{
+    constants::{
+        CONFIRM_NO, CONFIRM_YES, DELETE_BOARD, EDIT_TASK, EXIT, MOVE_DOWN, MOVE_UP, NEW_BOARD,
+        RENAME_BOARD, SEARCH, TAG_COLOR,
+    },
+    keymap::{Action, Keymap, format_keys},
+    theme::Theme,
+};
+
+/// Keys and what they do, ready to show
+pub type KeyHelp = (String, &'static str);
+
+fn key(keys: impl Into<String>, text: &'static str) -> KeyHelp {
+    (keys.into(), text)
+}
+
+/// Every board action with all its keys
+pub fn board_keys(keymap: &Keymap) -> Vec<KeyHelp> {
+    let mut keys = Vec::new();
+    for action in Action::ALL {
+        let bound: Vec<String> = keymap.keys(action).map(format_keys).collect();
+        if !bound.is_empty() {
+            keys.push(key(bound.join(", "), action.description()));
+        }
+        // Digits can't be rebound, they go with the other ways of changing columns
+        if action == Action::FocusRight {
+            keys.push(key("1-9", "Focus a column by its number"));
+        }
+    }
+    keys
+}
+
+pub fn editing_keys() -> Vec<KeyHelp> {
+    vec![
+        key("Enter", "Save"),
+        key("Alt-Enter", "New line"),
+        key("Esc", "Cancel"),
+        key("Ctrl-w", "Delete the word before the cursor"),
+        key("Ctrl-Left/Right", "Move by words"),
+        key("Home/End", "Start or end of the row"),
+        key("#tag !1 @friday", "Tags, priority and due date, in titles"),
+    ]
+}
+
+pub fn mouse_keys() -> Vec<KeyHelp> {
+    vec![
+        key("Click", "Focus a column or select a card"),
+        key("Double click", "Edit the card"),
+        key("Drag", "Move the card to another column or place"),
+        key("Wheel", "Select the card below or above"),
+    ]
+}
+
+pub fn search_keys(keymap: &Keymap) -> Vec<KeyHelp> {
+    let mut keys = vec![
+        key("Enter", "Go to the first match"),
+        key("Esc", "Clear the search"),
+        key("/regex/", "Search with a regex"),
+    ];
+    if let (Some(next), Some(previous)) = (
+        keymap.label(Action::NextMatch),
+        keymap.label(Action::PreviousMatch),
+    ) {
+        keys.push(key(format!("{next}/{previous}"), "Next or previous match"));
+    }
+    keys
+}
+
+pub fn detail_keys() -> Vec<KeyHelp> {
+    vec![
+        key(format!("{MOVE_DOWN}/{MOVE_UP}"), "Choose a field"),
+        key(format!("{EDIT_TASK}, Enter"), "Edit the field"),
+        key(format!("{EXIT}, Esc"), "Close"),
+    ]
+}
+
+pub fn archive_keys() -> Vec<KeyHelp> {
+    vec![
+        key(format!("{MOVE_DOWN}/{MOVE_UP}"), "Choose a card"),
+        key("Enter", "Restore the card to the column it came from"),
+        key("1-9", "Restore the card to a column by its number"),
+        key(SEARCH.to_string(), "Search the archive"),
+        key(format!("{EXIT}, Esc"), "Close"),
+    ]
+}
+
+pub fn board_picker_keys() -> Vec<KeyHelp> {
+    vec![
+        key(format!("{MOVE_DOWN}/{MOVE_UP}"), "Choose a board"),
+        key("Enter", "Open the board"),
+        key(NEW_BOARD.to_string(), "New board"),
+        key(RENAME_BOARD.to_string(), "Rename the board"),
+        key(DELETE_BOARD.to_string(), "Delete the board"),
+        key(format!("{EXIT}, Esc"), "Close"),
+    ]
+}
+
+pub fn tag_picker_keys() -> Vec<KeyHelp> {
+    vec![
+        key(format!("{MOVE_DOWN}/{MOVE_UP}"), "Choose a tag"),
+        key("Space, Enter", "Show or hide the tag"),
+        key(TAG_COLOR.to_string(), "Next color"),
+        key(format!("{EXIT}, Esc"), "Close"),
+    ]
+}
+
+pub fn confirm_keys() -> Vec<KeyHelp> {
+    vec![
+        key(format!("{CONFIRM_YES}, Enter"), "Yes, go ahead"),
+        key(format!("{CONFIRM_NO}, Esc"), "No, leave everything as is"),
+    ]
+}
+
+pub fn help_keys() -> Vec<KeyHelp> {
+    vec![
+        key(format!("{MOVE_DOWN}/{MOVE_UP}"), "Scroll"),
+        key("PageDown/PageUp", "Scroll a page"),
+        key(format!("{EXIT}, Esc"), "Close"),
+    ]
+}
+
+/// Scrollable popup listing every key, grouped by where it works
+#[derive(Debug, Default)]
+pub struct HelpPopup {
+    /// First line shown
+    scroll: usize,
+    /// Lines that fit in the popup on the last render
+    height: usize,
+}
+
+impl HelpPopup {
+    pub fn reset(&mut self) {
+        self.scroll = 0;
+    }
+
+    pub fn scroll_down(&mut self, lines: usize) {
+        self.scroll = self.scroll.saturating_add(lines);
+    }
+
+    pub fn scroll_up(&mut self, lines: usize) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+
+    /// Lines moved by PageDown and PageUp
+    pub fn page(&self) -> usize {
+        self.height.max(1)
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, keymap: &Keymap, theme: &Theme) {
+        let sections = [
+            ("Board", board_keys(keymap)),
+            ("Mouse", mouse_keys()),
+            ("Editing", editing_keys()),
+            ("Search", search_keys(keymap)),
+            ("Card details", detail_keys()),
+            ("Boards", board_picker_keys()),
+            ("Archive", archive_keys()),
+            ("Tag filter", tag_picker_keys()),
+            ("Confirmation", confirm_keys()),
+            ("Help", help_keys()),
+        ];
+        let width = sections
+            .iter()
+            .flat_map(|(_, keys)| keys.iter().map(|(keys, _)| keys.chars().count()))
+            .max()
+            .unwrap_or(0);
+
+        let mut lines = Vec::new();
+        for (title, keys) in sections {
+            if !lines.is_empty() {
+                lines.push(Line::default());
+            }
+            lines.push(Line::styled(
+                title,
+                Style::new()
+                    .fg(theme.popup_text)
+                    .add_modifier(Modifier::BOLD),
+            ));
+            for (keys, text) in keys {
+                lines.push(Line::raw(format!("  {keys:width$}  {text}")));
+            }
+        }
+
+        let block = Block::bordered()
+            .title(Line::raw("Keys").centered())
+            .border_style(theme.popup);
+        self.height = block.inner(area).height as usize;
+        self.scroll = self.scroll.min(lines.len().saturating_sub(self.height));
+
+        Widget::render(Clear, area, buf);
+        Paragraph::new(lines)
+            .block(block)
+            .scroll((self.scroll as u16, 0))
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_board_keys_follow_bindings() {
+        let keys = board_keys(&Keymap::default());
+        assert_eq!(keys[0], key("j, Down", "Select the card below"));
+        assert!(keys.contains(&key("gg", "Select the first card")));
+        assert!(keys.contains(&key("1-9", "Focus a column by its number")));
+    }
+
+    #[test]
+    fn test_scroll_stops_at_the_end() {
+        let mut help = HelpPopup::default();
+        let area = Rect::new(0, 0, 60, 10);
+        let mut buf = Buffer::empty(area);
+        help.scroll_down(usize::MAX);
+        help.render(area, &mut buf, &Keymap::default(), &Theme::default());
+        assert_eq!(help.page(), 8);
+        let last = help.scroll;
+
+        help.scroll_up(1);
+        assert_eq!(help.scroll, last - 1);
+        help.scroll_down(5);
+        help.render(area, &mut buf, &Keymap::default(), &Theme::default());
+        assert_eq!(help.scroll, last);
+    }
+}
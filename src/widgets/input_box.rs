@@ -1,15 +1,25 @@
+use std::ops::Range;
+
 use ratatui::{
     buffer::Buffer,
-    layout::Rect,
-    style::Color,
-    widgets::{Block, Clear, Paragraph, Widget, Wrap},
+    layout::{Position, Rect},
+    text::Line,
+    widgets::{Block, Clear, Paragraph, Widget},
 };
 
+use crate::theme::Theme;
+
 pub struct InputBox {
     /// Current value of the input box
     input: String,
     /// Position of cursor in the editor area.
     character_index: usize,
+    /// Title shown on the popup border. Tells the user what the input will be used for
+    title: String,
+    /// Width of the text area on the last render. Used to move the cursor across wrapped rows
+    width: usize,
+    /// First row shown, so the cursor stays visible when the text is taller than the box
+    scroll: usize,
 }
 
 impl Default for InputBox {
@@ -23,9 +33,32 @@ impl InputBox {
         InputBox {
             input: String::new(),
             character_index: 0,
+            title: String::from("Input"),
+            width: usize::MAX,
+            scroll: 0,
         }
     }
 
+    pub fn set_title(&mut self, title: &str) {
+        self.title = title.to_string();
+    }
+
+    /// Replaces the current value and leaves the cursor at the end of it
+    pub fn set_input(&mut self, input: String) {
+        self.character_index = input.chars().count();
+        self.input = input;
+    }
+
+    pub fn value(&self) -> &str {
+        &self.input
+    }
+
+    pub fn clear(&mut self) {
+        self.input.clear();
+        self.reset_cursor();
+        self.scroll = 0;
+    }
+
     pub fn move_cursor_left(&mut self) {
         let cursor_moved_left = self.character_index.saturating_sub(1);
         self.character_index = self.clamp_cursor(cursor_moved_left);
@@ -34,7 +67,59 @@ impl InputBox {
     pub fn move_cursor_right(&mut self) {
         let cursor_moved_right = self.character_index.saturating_add(1);
         self.character_index = self.clamp_cursor(cursor_moved_right);
-        // TODO: Hay que mover el cursor para la siguiente linea si la tarea es muy larga
+    }
+
+    /// Moves the cursor to the same column of the row above, or to the start of the text
+    pub fn move_cursor_up(&mut self) {
+        let rows = self.rows();
+        let (row, column) = self.cursor_row(&rows);
+        self.character_index = match row.checked_sub(1) {
+            Some(above) => self.index_in_row(&rows, above, column),
+            None => 0,
+        };
+    }
+
+    /// Moves the cursor to the same column of the row below, or to the end of the text
+    pub fn move_cursor_down(&mut self) {
+        let rows = self.rows();
+        let (row, column) = self.cursor_row(&rows);
+        self.character_index = if row + 1 < rows.len() {
+            self.index_in_row(&rows, row + 1, column)
+        } else {
+            self.input.chars().count()
+        };
+    }
+
+    /// Moves the cursor to the start of its row
+    pub fn move_cursor_home(&mut self) {
+        let rows = self.rows();
+        let (row, _) = self.cursor_row(&rows);
+        self.character_index = rows[row].start;
+    }
+
+    /// Moves the cursor to the end of its row
+    pub fn move_cursor_end(&mut self) {
+        let rows = self.rows();
+        let (row, _) = self.cursor_row(&rows);
+        self.character_index = self.index_in_row(&rows, row, usize::MAX);
+    }
+
+    /// Moves the cursor to the start of the word on its left
+    pub fn move_word_left(&mut self) {
+        self.character_index = self.word_start();
+    }
+
+    /// Moves the cursor to the end of the word on its right
+    pub fn move_word_right(&mut self) {
+        let chars: Vec<char> = self.input.chars().collect();
+        let mut index = self.character_index;
+        while index < chars.len() && chars[index].is_whitespace() {
+            index += 1;
+        }
+        while index < chars.len() && !chars[index].is_whitespace() {
+            index += 1;
+        }
+        self.character_index = index;
     }
 
     pub fn enter_char(&mut self, new_char: char) {
@@ -77,6 +162,23 @@ impl InputBox {
         }
     }
 
+    /// Deletes the character under the cursor, the one the Delete key points at
+    pub fn delete_char_forward(&mut self) {
+        if self.character_index < self.input.chars().count() {
+            self.move_cursor_right();
+            self.delete_char();
+        }
+    }
+
+    /// Deletes from the start of the word on the left of the cursor up to the cursor
+    pub fn delete_word(&mut self) {
+        let start = self.word_start();
+        let before = self.input.chars().take(start);
+        let after = self.input.chars().skip(self.character_index);
+        self.input = before.chain(after).collect();
+        self.character_index = start;
+    }
+
     pub fn clamp_cursor(&self, new_cursor_pos: usize) -> usize {
         new_cursor_pos.clamp(0, self.input.chars().count())
     }
@@ -90,24 +192,199 @@ impl InputBox {
             return None;
         }
         let result = self.input.clone();
-        self.input.clear();
-        self.reset_cursor();
+        self.clear();
         Some(result)
     }
 
     pub fn get_char_index(&self) -> usize {
         self.character_index
     }
+
+    /// Where the terminal cursor goes when the box was rendered on `area`
+    pub fn cursor_position(&self, area: Rect) -> Position {
+        let rows = self.rows();
+        let (row, column) = self.cursor_row(&rows);
+        // A cursor right after a full row is drawn at the start of the next one
+        let (row, column) = if column >= self.width {
+            (row + 1, 0)
+        } else {
+            (row, column)
+        };
+        Position::new(
+            // Skip the left border
+            area.x + 1 + column as u16,
+            area.y + 1 + row.saturating_sub(self.scroll) as u16,
+        )
+    }
+
+    /// Visual rows of the text for the last rendered width
+    fn rows(&self) -> Vec<Range<usize>> {
+        wrap(&self.input, self.width)
+    }
+
+    /// Row and column of the cursor inside `rows`
+    fn cursor_row(&self, rows: &[Range<usize>]) -> (usize, usize) {
+        let index = self.character_index;
+        let row = (0..rows.len())
+            .find(|&row| index < rows[row].end || index == self.row_end(rows, row))
+            .unwrap_or(rows.len() - 1);
+        (row, index - rows[row].start)
+    }
+
+    /// Last cursor position inside `row`. The end of a row that wraps into the next one belongs
+    /// to the next row, so the cursor stops one character before it
+    fn row_end(&self, rows: &[Range<usize>], row: usize) -> usize {
+        match rows.get(row + 1) {
+            Some(next) if next.start == rows[row].end => rows[row].end - 1,
+            _ => rows[row].end,
+        }
+    }
+
+    /// Text index at `column` of `row`, clamped to the row
+    fn index_in_row(&self, rows: &[Range<usize>], row: usize, column: usize) -> usize {
+        (rows[row].start + column).min(self.row_end(rows, row))
+    }
+
+    /// Start of the word on the left of the cursor, skipping the spaces right before it
+    fn word_start(&self) -> usize {
+        let chars: Vec<char> = self.input.chars().collect();
+        let mut index = self.character_index;
+        while index > 0 && chars[index - 1].is_whitespace() {
+            index -= 1;
+        }
+        while index > 0 && !chars[index - 1].is_whitespace() {
+            index -= 1;
+        }
+        index
+    }
 }
 
-impl Widget for &mut InputBox {
-    fn render(self, area: Rect, buf: &mut Buffer) {
-        let input = Paragraph::new(self.input.as_str())
-            .wrap(Wrap { trim: true }) // TODO: Estudiar bien que hace esta linea
-            .style(Color::Red)
-            .block(Block::bordered().title("Input"));
+/// Splits `text` in rows of at most `width` characters, breaking after a space when a row is
+/// too long and cutting words only when they don't fit in a row of their own. Each row is the
+/// range of character indexes it shows; line breaks are not part of any row.
+fn wrap(text: &str, width: usize) -> Vec<Range<usize>> {
+    let width = width.max(1);
+    let chars: Vec<char> = text.chars().collect();
+    let mut rows = Vec::new();
+    let mut line_start = 0;
+
+    for line in text.split('\n') {
+        let line_end = line_start + line.chars().count();
+        let mut start = line_start;
+        while line_end - start > width {
+            let end = (start + 1..=start + width)
+                .rev()
+                .find(|&end| chars[end - 1].is_whitespace())
+                .unwrap_or(start + width);
+            rows.push(start..end);
+            start = end;
+        }
+        rows.push(start..line_end);
+        // Skip the line break
+        line_start = line_end + 1;
+    }
+    rows
+}
+
+impl InputBox {
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let block = Block::bordered()
+            .title(self.title.as_str())
+            .border_style(theme.popup);
+        let inner = block.inner(area);
+        self.width = inner.width as usize;
+
+        let rows = self.rows();
+        let (mut cursor_row, column) = self.cursor_row(&rows);
+        if column >= self.width {
+            cursor_row += 1;
+        }
+        // Scroll just enough to keep the cursor inside the box
+        let height = (inner.height as usize).max(1);
+        self.scroll = self
+            .scroll
+            .clamp(cursor_row.saturating_sub(height - 1), cursor_row);
+
+        let chars: Vec<char> = self.input.chars().collect();
+        let lines: Vec<Line> = rows
+            .iter()
+            .skip(self.scroll)
+            .map(|row| Line::raw(chars[row.clone()].iter().collect::<String>()))
+            .collect();
+        let input = Paragraph::new(lines).style(theme.input).block(block);
 
         Widget::render(Clear, area, buf);
         Widget::render(input, area, buf);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input_box(text: &str, width: usize) -> InputBox {
+        let mut input_box = InputBox::new();
+        input_box.set_input(text.to_string());
+        input_box.width = width;
+        input_box
+    }
+
+    #[test]
+    fn test_wrap_breaks_after_spaces_and_lines() {
+        assert_eq!(wrap("hello big world", 10), [0..10, 10..15]);
+        assert_eq!(wrap("abcdefghij", 4), [0..4, 4..8, 8..10]);
+        assert_eq!(wrap("one\n\ntwo", 10), [0..3, 4..4, 5..8]);
+        assert_eq!(wrap("", 10), vec![0..0]);
+    }
+
+    #[test]
+    fn test_cursor_moves_across_wrapped_rows() {
+        // Rows: "hello big " and "world"
+        let mut input_box = input_box("hello big world", 10);
+        assert_eq!(
+            input_box.cursor_position(Rect::new(0, 0, 12, 5)),
+            Position::new(6, 2)
+        );
+
+        input_box.move_cursor_up();
+        assert_eq!(input_box.get_char_index(), 5);
+        input_box.move_cursor_end();
+        assert_eq!(input_box.get_char_index(), 9);
+        input_box.move_cursor_down();
+        assert_eq!(input_box.get_char_index(), 15);
+        input_box.move_cursor_home();
+        assert_eq!(input_box.get_char_index(), 10);
+    }
+
+    #[test]
+    fn test_newlines_and_vertical_movement() {
+        let mut input_box = input_box("first", 20);
+        input_box.enter_char('\n');
+        input_box.enter_char('x');
+        assert_eq!(
+            input_box.cursor_position(Rect::new(0, 0, 22, 5)),
+            Position::new(2, 2)
+        );
+
+        input_box.move_cursor_up();
+        assert_eq!(input_box.get_char_index(), 1);
+        input_box.move_cursor_up();
+        assert_eq!(input_box.get_char_index(), 0);
+    }
+
+    #[test]
+    fn test_word_movement_and_deletion() {
+        let mut input_box = input_box("fix the  bug", 20);
+        input_box.delete_word();
+        assert_eq!(input_box.input, "fix the  ");
+        input_box.delete_word();
+        assert_eq!(input_box.input, "fix ");
+
+        input_box.move_word_left();
+        assert_eq!(input_box.get_char_index(), 0);
+        input_box.move_word_right();
+        assert_eq!(input_box.get_char_index(), 3);
+        input_box.delete_char_forward();
+        assert_eq!(input_box.input, "fix");
+    }
+}
@@ -1,20 +1,82 @@
 use ratatui::{
     buffer::Buffer,
-    layout::Rect,
-    style::{Color, Stylize},
-    text::Line,
-    widgets::Widget,
-    widgets::{Block, HighlightSpacing, List, ListItem, ListState, StatefulWidget},
+    layout::{Position, Rect},
+    style::{Modifier, Stylize},
+    text::{Line, Span, Text},
+    widgets::{
+        Block, BorderType, HighlightSpacing, List, ListItem, ListState, Paragraph, StatefulWidget,
+        Widget,
+    },
 };
 
-use crate::constants::HIGHLIGHT_SIMBOL;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::{FOCUS_MARKER, HIGHLIGHT_SIMBOL, UNTITLED_COLUMN},
+    due::{Urgency, format_due, today, urgency},
+    filter::Filter,
+    search::Search,
+    tags::Palette,
+    task::Task,
+    theme::Theme,
+};
+
+/// Order the cards of a column are shown in. Sorting never changes the stored order, so going
+/// back to manual restores it
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortMode {
+    /// The order the user gave the cards
+    #[default]
+    Manual,
+    /// Most urgent first, cards without priority last
+    Priority,
+    /// Oldest first
+    Created,
+    /// Soonest first, cards without due date last
+    Due,
+}
+
+impl SortMode {
+    /// The mode after this one, wrapping around
+    pub fn next(self) -> Self {
+        match self {
+            SortMode::Manual => SortMode::Priority,
+            SortMode::Priority => SortMode::Created,
+            SortMode::Created => SortMode::Due,
+            SortMode::Due => SortMode::Manual,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortMode::Manual => "manual",
+            SortMode::Priority => "priority",
+            SortMode::Created => "created",
+            SortMode::Due => "due date",
+        }
+    }
+}
 
-/// A widget use to represent one of the three kanban columns (todo, doing, done)
+/// A widget use to represent one of the user defined kanban columns
 #[derive(Debug, Clone)]
 pub struct KanbanColumn {
-    items: Vec<String>,
+    items: Vec<Task>,
+    /// Scroll position of the list. The selection itself lives in `selected`
     state: ListState,
     title: String,
+    /// Index inside `items` of the selected card
+    selected: Option<usize>,
+    /// Cards that don't match are not drawn and the selection skips them
+    filter: Filter,
+    sort: SortMode,
+    /// Most cards the column should hold, the WIP limit
+    limit: Option<usize>,
+    /// Where the column was last drawn, to find what the mouse points at
+    area: Rect,
+    /// Item index and height of the shown cards on the last render, top to bottom
+    rows: Vec<(usize, u16)>,
 }
 
 impl KanbanColumn {
@@ -26,6 +88,12 @@ impl KanbanColumn {
             items: Vec::new(),
             state: ListState::default(),
             title,
+            selected: None,
+            filter: Filter::default(),
+            sort: SortMode::default(),
+            limit: None,
+            area: Rect::default(),
+            rows: Vec::new(),
         };
 
         // Postcondition: ensure proper initialization
@@ -39,84 +107,365 @@ impl KanbanColumn {
         column
     }
 
-    pub fn load(&mut self, items: Vec<String>) {
+    pub fn load(&mut self, items: Vec<Task>) {
         self.items = items
     }
 
-    pub fn to_json(&self) -> Vec<String> {
+    pub fn to_json(&self) -> Vec<Task> {
         self.items.clone()
     }
 
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn rename(&mut self, title: String) {
+        // Precondition: title should not be empty
+        assert!(!title.is_empty(), "Column title cannot be empty");
+        self.title = title;
+    }
+
+    /// Index of the selected card. May point past the last card until the column is drawn
     pub fn selected(&self) -> Option<usize> {
-        self.state.selected()
+        self.selected
+    }
+
+    pub fn select(&mut self, index: Option<usize>) {
+        self.selected = index;
     }
 
     pub fn clear_select(&mut self) {
-        self.state.select(None);
+        self.selected = None;
     }
 
+    /// Selects the card shown below the selected one, or the first one when nothing is selected
     pub fn select_next(&mut self) {
-        self.state.select_next()
+        let visible = self.visible();
+        let row = self.selected_row(&visible);
+        self.selected = match (self.selected, row) {
+            (None, _) => visible.first().copied(),
+            (Some(_), Some(row)) => visible.get(row + 1).or(visible.get(row)).copied(),
+            // Drawing the column moves it to a visible card
+            (Some(i), None) => Some(i),
+        };
     }
 
+    /// Selects the card shown above the selected one, or the last one when nothing is selected
     pub fn select_previous(&mut self) {
-        self.state.select_previous()
+        let visible = self.visible();
+        let row = self.selected_row(&visible);
+        self.selected = match (self.selected, row) {
+            (None, _) => visible.last().copied(),
+            (Some(_), Some(row)) => visible.get(row.saturating_sub(1)).copied(),
+            (Some(i), None) => Some(i),
+        };
+    }
+
+    /// Selects the first card shown
+    pub fn select_first(&mut self) {
+        self.selected = self.visible().first().copied();
+    }
+
+    /// Selects the last card shown
+    pub fn select_last(&mut self) {
+        self.selected = self.visible().last().copied();
+    }
+
+    /// Display row of the selected card, when it is shown
+    pub fn selected_position(&self) -> Option<usize> {
+        self.selected_row(&self.visible())
+    }
+
+    /// Index of the card shown at display `row`, or of the last shown card past the end
+    pub fn shown_at(&self, row: usize) -> Option<usize> {
+        let visible = self.visible();
+        visible.get(row).or(visible.last()).copied()
+    }
+
+    /// Display row and item index of the shown cards matching `search`, top to bottom
+    pub fn search(&self, search: &Search) -> Vec<(usize, usize)> {
+        self.visible()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, i)| search.matches(&self.items[*i]))
+            .collect()
+    }
+
+    /// Whether `position` falls inside the column as last drawn
+    pub fn contains(&self, position: Position) -> bool {
+        self.area.contains(position)
     }
 
-    pub fn push(&mut self, task: String) {
+    /// Index of the card drawn at `position`, if any
+    pub fn card_at(&self, position: Position) -> Option<usize> {
+        let inner = Block::bordered().inner(self.area);
+        if !inner.contains(position) {
+            return None;
+        }
+        let mut top = inner.y;
+        for (i, height) in self.rows.iter().skip(self.state.offset()) {
+            if position.y < top + height {
+                return Some(*i);
+            }
+            top += height;
+        }
+        None
+    }
+
+    /// Row of the selected card among the visible ones
+    fn selected_row(&self, visible: &[usize]) -> Option<usize> {
+        self.selected
+            .and_then(|i| visible.iter().position(|v| *v == i))
+    }
+
+    pub fn sort(&self) -> SortMode {
+        self.sort
+    }
+
+    pub fn set_sort(&mut self, sort: SortMode) {
+        self.sort = sort;
+    }
+
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+
+    pub fn set_limit(&mut self, limit: Option<usize>) {
+        self.limit = limit;
+    }
+
+    /// True when one more card would take the column over its limit
+    pub fn is_full(&self) -> bool {
+        self.limit.is_some_and(|limit| self.items.len() >= limit)
+    }
+
+    pub fn is_over_limit(&self) -> bool {
+        self.limit.is_some_and(|limit| self.items.len() > limit)
+    }
+
+    pub fn set_filter(&mut self, filter: &Filter) {
+        if self.filter != *filter {
+            self.filter = filter.clone();
+        }
+    }
+
+    /// Indexes of the cards that pass the filter, in the order they are shown
+    fn visible(&self) -> Vec<usize> {
+        let mut visible: Vec<usize> = (0..self.items.len())
+            .filter(|i| self.filter.matches(&self.items[*i]))
+            .collect();
+        match self.sort {
+            SortMode::Manual => {}
+            // Stable sorts, so ties keep the manual order
+            SortMode::Priority => visible
+                .sort_by_key(|i| (self.items[*i].priority.is_none(), self.items[*i].priority)),
+            SortMode::Created => visible.sort_by_key(|i| self.items[*i].created_at),
+            SortMode::Due => {
+                visible.sort_by_key(|i| (self.items[*i].due.is_none(), self.items[*i].due))
+            }
+        }
+        visible
+    }
+
+    pub fn push(&mut self, task: Task) {
         self.items.push(task)
     }
 
-    pub fn remove(&mut self, i: usize) -> String {
+    pub fn remove(&mut self, i: usize) -> Task {
         self.items.remove(i)
     }
 
-    fn render(&mut self, area: Rect, buf: &mut Buffer) {
-        let block = Block::bordered().title(Line::raw(self.title.clone()).centered());
+    pub fn insert(&mut self, i: usize, task: Task) {
+        self.items.insert(i, task)
+    }
+
+    /// Puts `task` in place of the one at `i`, returning the old one
+    pub fn replace(&mut self, i: usize, task: Task) -> Task {
+        std::mem::replace(&mut self.items[i], task)
+    }
+
+    pub fn get(&self, i: usize) -> Option<&Task> {
+        self.items.get(i)
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Draws the shown cards, with the ones matching `search` highlighted and their tags in the
+    /// colors of `palette`. `focused` tells whether this is the column keys act on
+    pub fn render(
+        &mut self,
+        area: Rect,
+        buf: &mut Buffer,
+        palette: &Palette,
+        search: Option<&Search>,
+        theme: &Theme,
+        focused: bool,
+    ) {
+        self.area = area;
+        self.rows.clear();
+        let mut title = if focused {
+            format!("{FOCUS_MARKER}{}", self.title)
+        } else {
+            self.title.clone()
+        };
+        if let Some(limit) = self.limit {
+            title = format!("{title} ({}/{limit})", self.items.len());
+        }
+        if self.sort != SortMode::Manual {
+            title = format!("{title} (by {})", self.sort.label());
+        }
+        let border = if self.is_over_limit() {
+            theme.over_limit
+        } else if focused {
+            theme.focused
+        } else {
+            theme.border
+        };
+        let mut block = Block::bordered()
+            .title(Line::raw(title).centered())
+            .border_style(border);
+        if focused {
+            block = block
+                .border_type(BorderType::Thick)
+                .title_style(Modifier::BOLD);
+        }
 
         let available_width = calculate_available_width(area, &block);
 
-        let items: Vec<ListItem> = self
-            .items
+        // Like a plain list, a selection past the end or on a hidden card moves to the closest
+        // visible card
+        let visible = self.visible();
+        self.selected = self.selected.and_then(|i| {
+            visible
+                .iter()
+                .copied()
+                .find(|v| *v >= i)
+                .or(visible.last().copied())
+        });
+        // Other columns keep their selection for when the focus comes back, without showing it
+        self.state
+            .select(self.selected_row(&visible).filter(|_| focused));
+
+        if visible.is_empty() {
+            let placeholder = if self.items.is_empty() {
+                "No cards"
+            } else {
+                "No cards match the filter"
+            };
+            Paragraph::new(Line::raw(placeholder).dim().centered())
+                .block(block)
+                .render(area, buf);
+            return;
+        }
+
+        let today = today();
+        let items: Vec<ListItem> = visible
             .iter()
-            .map(|item| {
-                let fit_item = fit_to_width(item, available_width);
-                ListItem::from(fit_item)
+            .map(|i| {
+                let item = &self.items[*i];
+                let mut text = match item.priority {
+                    Some(priority) => {
+                        let marker = format!("{priority} ");
+                        let width = available_width.saturating_sub(marker.len());
+                        let mut text = Text::raw(fit_to_width(&item.title, width));
+                        if let Some(first) = text.lines.first_mut() {
+                            first
+                                .spans
+                                .insert(0, Span::raw(marker).bold().fg(theme.priority(priority)));
+                        }
+                        text
+                    }
+                    None => Text::raw(fit_to_width(&item.title, available_width)),
+                };
+                if item.due.is_some() || !item.tags.is_empty() {
+                    text.push_line(details_line(item, palette, theme, today));
+                }
+                self.rows.push((*i, text.height() as u16));
+                let item = ListItem::from(text);
+                if search.is_some_and(|search| search.matches(&self.items[*i])) {
+                    // Underlined too, so matches show without colors
+                    item.bg(theme.search_match).underlined()
+                } else {
+                    item
+                }
             })
             .collect();
 
         let list = List::new(items)
             .block(block)
             .highlight_symbol(HIGHLIGHT_SIMBOL)
-            .highlight_style(Color::White)
-            .fg(Color::Yellow)
+            .highlight_style(theme.selected)
+            .fg(theme.text)
             .highlight_spacing(HighlightSpacing::Always);
 
         StatefulWidget::render(list, area, buf, &mut self.state);
     }
 }
 
+/// `title` as read from disk, or `UNTITLED_COLUMN` when it is blank. Titles typed in the app
+/// are never empty, but a hand edited file may have them
+pub fn loaded_title(title: String) -> String {
+    if title.trim().is_empty() {
+        UNTITLED_COLUMN.to_string()
+    } else {
+        title
+    }
+}
+
+/// Line under the title with the due date, colored by how close it is, and the tags drawn as
+/// small blocks of their palette color
+fn details_line(task: &Task, palette: &Palette, theme: &Theme, today: NaiveDate) -> Line<'static> {
+    let mut spans = Vec::new();
+    if let Some(due) = task.due {
+        let urgency = urgency(due, today);
+        let text = Span::raw(format!("due {}", format_due(due, today))).fg(theme.urgency(urgency));
+        spans.push(match urgency {
+            Urgency::Overdue => text.bold(),
+            _ => text,
+        });
+    }
+    for tag in &task.tags {
+        if !spans.is_empty() {
+            spans.push(Span::raw(" "));
+        }
+        spans.push(Span::styled(
+            format!(" {tag} "),
+            theme.tag(palette.color(tag)),
+        ));
+    }
+    Line::from(spans)
+}
+
 fn calculate_available_width(area: Rect, block: &Block<'_>) -> usize {
     let block_width = block.inner(area);
     let highlight_width = HIGHLIGHT_SIMBOL.len() as u16;
     block_width.width.saturating_sub(highlight_width) as usize
 }
 
+/// Cuts every line of `s` in pieces of `width` characters
 fn fit_to_width(s: &str, width: usize) -> String {
-    s.chars()
-        .collect::<Vec<_>>()
-        .chunks(width)
-        .map(|chunk| chunk.iter().collect::<String>())
+    s.lines()
+        .flat_map(|line| {
+            let chars = line.chars().collect::<Vec<_>>();
+            // Chunks of an empty line are no lines at all, keep it as a blank one
+            if chars.is_empty() {
+                return vec![String::new()];
+            }
+            chars
+                .chunks(width.max(1))
+                .map(|chunk| chunk.iter().collect::<String>())
+                .collect()
+        })
         .collect::<Vec<_>>()
         .join("\n")
 }
 
-impl Widget for &mut KanbanColumn {
-    fn render(self, area: Rect, buf: &mut Buffer) {
-        self.render(area, buf);
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -132,29 +481,29 @@ mod tests {
     #[test]
     fn test_push_item() {
         let mut column = KanbanColumn::new("Test".to_string());
-        column.push("Task 1".to_string());
+        column.push(Task::new("Task 1".to_string()));
         assert_eq!(column.items.len(), 1);
-        assert_eq!(column.items[0], "Task 1");
+        assert_eq!(column.items[0].title, "Task 1");
     }
 
     #[test]
     fn test_remove_item() {
         let mut column = KanbanColumn::new("Test".to_string());
-        column.push("Task 1".to_string());
-        column.push("Task 2".to_string());
+        column.push(Task::new("Task 1".to_string()));
+        column.push(Task::new("Task 2".to_string()));
 
         let removed = column.remove(0);
-        assert_eq!(removed, "Task 1");
+        assert_eq!(removed.title, "Task 1");
         assert_eq!(column.items.len(), 1);
-        assert_eq!(column.items[0], "Task 2");
+        assert_eq!(column.items[0].title, "Task 2");
     }
 
     #[test]
     fn test_selection_navigation() {
         let mut column = KanbanColumn::new("Test".to_string());
-        column.push("Task 1".to_string());
-        column.push("Task 2".to_string());
-        column.push("Task 3".to_string());
+        column.push(Task::new("Task 1".to_string()));
+        column.push(Task::new("Task 2".to_string()));
+        column.push(Task::new("Task 3".to_string()));
 
         // Test initial selection
         assert_eq!(column.selected(), None);
@@ -174,4 +523,144 @@ mod tests {
         column.clear_select();
         assert_eq!(column.selected(), None);
     }
+
+    #[test]
+    fn test_selection_skips_filtered_cards() {
+        let mut column = KanbanColumn::new("Test".to_string());
+        column.push(Task::from_input("Task 1 #bug"));
+        column.push(Task::from_input("Task 2"));
+        column.push(Task::from_input("Task 3 #bug"));
+
+        let mut filter = Filter::default();
+        filter.toggle_tag("bug");
+        column.set_filter(&filter);
+
+        column.select_next();
+        assert_eq!(column.selected(), Some(0));
+        column.select_next();
+        assert_eq!(column.selected(), Some(2));
+        column.select_next();
+        assert_eq!(column.selected(), Some(2));
+        column.select_previous();
+        assert_eq!(column.selected(), Some(0));
+
+        assert_eq!(column.shown_at(1), Some(2));
+        assert_eq!(column.shown_at(usize::MAX), Some(2));
+    }
+
+    #[test]
+    fn test_sorted_selection_follows_display_order() {
+        let mut column = KanbanColumn::new("Test".to_string());
+        column.push(Task::from_input("Task 1"));
+        column.push(Task::from_input("Task 2 !2"));
+        column.push(Task::from_input("Task 3 !0"));
+
+        column.set_sort(SortMode::Priority);
+        assert_eq!(column.visible(), [2, 1, 0]);
+
+        column.select_next();
+        assert_eq!(column.selected(), Some(2));
+        column.select_next();
+        assert_eq!(column.selected(), Some(1));
+        column.select_previous();
+        assert_eq!(column.selected(), Some(2));
+
+        column.set_sort(SortMode::Manual);
+        assert_eq!(column.visible(), [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_search_follows_display_order() {
+        let mut column = KanbanColumn::new("TODO".to_string());
+        column.push(Task::new("Fix login".to_string()));
+        column.push(Task::new("Write docs".to_string()));
+        column.push(Task::from_input("Fix signup !0"));
+        let search = Search::parse("fix").unwrap().unwrap();
+        assert_eq!(column.search(&search), [(0, 0), (2, 2)]);
+
+        column.set_sort(SortMode::Priority);
+        assert_eq!(column.search(&search), [(0, 2), (1, 0)]);
+    }
+
+    /// Text of every row of `column` drawn in a 30x4 area
+    fn draw(column: &mut KanbanColumn, focused: bool) -> Vec<String> {
+        let area = Rect::new(0, 0, 30, 4);
+        let mut buf = Buffer::empty(area);
+        column.render(
+            area,
+            &mut buf,
+            &Palette::default(),
+            None,
+            &Theme::default(),
+            focused,
+        );
+        (0..area.height)
+            .map(|y| (0..area.width).map(|x| buf[(x, y)].symbol()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_focus_marker_and_placeholder() {
+        let mut column = KanbanColumn::new("TODO".to_string());
+        let rows = draw(&mut column, true);
+        assert!(rows[0].contains(&format!("{FOCUS_MARKER}TODO")));
+        assert!(rows[1].contains("No cards"));
+        assert!(!draw(&mut column, false)[0].contains(FOCUS_MARKER));
+
+        column.push(Task::new("Task 1".to_string()));
+        column.set_filter(&Filter {
+            due_by: NaiveDate::from_ymd_opt(2000, 1, 1),
+            ..Filter::default()
+        });
+        assert!(draw(&mut column, true)[1].contains("No cards match the filter"));
+    }
+
+    #[test]
+    fn test_unfocused_column_keeps_its_selection() {
+        let mut column = KanbanColumn::new("TODO".to_string());
+        column.push(Task::new("Task 1".to_string()));
+        column.push(Task::new("Task 2".to_string()));
+        column.select(Some(1));
+
+        draw(&mut column, false);
+        assert_eq!(column.state.selected(), None);
+        assert_eq!(column.selected(), Some(1));
+        draw(&mut column, true);
+        assert_eq!(column.state.selected(), Some(1));
+    }
+
+    #[test]
+    fn test_card_at_follows_card_heights() {
+        let mut column = KanbanColumn::new("TODO".to_string());
+        column.push(Task::from_input("Task 1 #bug"));
+        column.push(Task::new("Task 2".to_string()));
+        column.push(Task::new("Task 3".to_string()));
+        draw(&mut column, true);
+
+        // The border takes the first row and the tags line of the first card the third one
+        assert_eq!(column.card_at(Position::new(5, 0)), None);
+        assert_eq!(column.card_at(Position::new(5, 1)), Some(0));
+        assert_eq!(column.card_at(Position::new(5, 2)), Some(0));
+        assert!(column.contains(Position::new(29, 3)));
+        assert!(!column.contains(Position::new(30, 3)));
+
+        column.select(Some(2));
+        draw(&mut column, true);
+        assert_eq!(column.card_at(Position::new(5, 1)), Some(1));
+        assert_eq!(column.card_at(Position::new(5, 2)), Some(2));
+    }
+
+    #[test]
+    fn test_wip_limit() {
+        let mut column = KanbanColumn::new("Doing".to_string());
+        column.set_limit(Some(1));
+        assert!(!column.is_full());
+
+        column.push(Task::new("Task 1".to_string()));
+        assert!(column.is_full());
+        assert!(!column.is_over_limit());
+
+        column.push(Task::new("Task 2".to_string()));
+        assert!(column.is_over_limit());
+    }
 }
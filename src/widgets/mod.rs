@@ -1,3 +1,9 @@
+pub mod archive_view;
+pub mod board_picker;
+pub mod card_detail;
+pub mod confirm;
 pub mod footer;
+pub mod help;
 pub mod input_box;
 pub mod kanban_column;
+pub mod tag_picker;
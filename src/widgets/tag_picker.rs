@@ -0,0 +1,73 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Clear, HighlightSpacing, List, ListState, StatefulWidget, Widget},
+};
+
+use crate::{constants::HIGHLIGHT_SIMBOL, filter::Filter, tags::Palette, theme::Theme};
+
+/// Popup listing the tags of the board. Tags can be added to the filter and recolored from it
+#[derive(Debug, Default)]
+pub struct TagPicker {
+    /// Tag, its color and whether the filter uses it
+    tags: Vec<(String, Color, bool)>,
+    state: ListState,
+}
+
+impl TagPicker {
+    /// Lists every tag of `palette`, keeping the selection on the same row
+    pub fn load(&mut self, palette: &Palette, filter: &Filter) {
+        self.tags = palette
+            .tags()
+            .map(|tag| {
+                (
+                    tag.to_string(),
+                    palette.color(tag),
+                    filter.tags.contains(tag),
+                )
+            })
+            .collect();
+        if self.state.selected().is_none() {
+            self.state.select(Some(0));
+        }
+    }
+
+    pub fn selected_tag(&self) -> Option<&str> {
+        self.state
+            .selected()
+            .and_then(|i| self.tags.get(i))
+            .map(|(tag, _, _)| tag.as_str())
+    }
+
+    pub fn select_next(&mut self) {
+        self.state.select_next()
+    }
+
+    pub fn select_previous(&mut self) {
+        self.state.select_previous()
+    }
+
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let block = Block::bordered()
+            .title(Line::raw("Filter by tag").centered())
+            .border_style(theme.popup);
+        let items = self.tags.iter().map(|(tag, color, active)| {
+            let check = if *active { "[x] " } else { "[ ] " };
+            Line::from(vec![
+                Span::raw(check),
+                Span::styled(format!(" {tag} "), theme.tag(*color)),
+            ])
+        });
+        let list = List::new(items)
+            .block(block)
+            .highlight_symbol(HIGHLIGHT_SIMBOL)
+            .highlight_style(theme.selected)
+            .fg(theme.popup_text)
+            .highlight_spacing(HighlightSpacing::Always);
+
+        Widget::render(Clear, area, buf);
+        StatefulWidget::render(list, area, buf, &mut self.state);
+    }
+}
@@ -0,0 +1,49 @@
+use color_eyre::{Result, eyre::eyre};
+use std::{env, str::FromStr};
+
+/// Environment variable used to pick the WIP policy when no flag is given
+pub const WIP_POLICY_ENV: &str = "KANBAN_WIP_POLICY";
+
+/// What happens when a card would take a column over its WIP limit
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum WipPolicy {
+    /// The card goes in anyway and a warning is shown
+    #[default]
+    Warn,
+    /// The card stays where it was
+    Refuse,
+}
+
+impl FromStr for WipPolicy {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "warn" => Ok(WipPolicy::Warn),
+            "refuse" => Ok(WipPolicy::Refuse),
+            _ => Err(eyre!("Unknown WIP policy '{s}', expected warn or refuse")),
+        }
+    }
+}
+
+impl WipPolicy {
+    /// Policy chosen with the `--wip-policy` flag, then the `KANBAN_WIP_POLICY` variable
+    pub fn resolve(flag: Option<WipPolicy>) -> Result<WipPolicy> {
+        match (flag, env::var(WIP_POLICY_ENV)) {
+            (Some(policy), _) => Ok(policy),
+            (None, Ok(value)) if !value.is_empty() => value.parse(),
+            _ => Ok(WipPolicy::default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_policy() {
+        assert_eq!("Refuse".parse::<WipPolicy>().unwrap(), WipPolicy::Refuse);
+        assert!("ignore".parse::<WipPolicy>().is_err());
+    }
+}